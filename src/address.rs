@@ -2,8 +2,10 @@ use bitcoin::secp256k1::PublicKey;
 use bitcoin::hashes::{sha256, ripemd160, Hash};
 use bitcoin::base58;
 use std::fmt;
+use std::str::FromStr;
 
 use crate::network::Network;
+use crate::wif::{self, WifError};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressKind {
@@ -16,6 +18,11 @@ pub enum AddressError {
     InvalidBase58Check(String),
     InvalidLength(usize),
     UnknownVersionByte(u8),
+    WrongLength(usize),
+    InvalidWif(WifError),
+    WrongNetwork { expected: Network, found: Network },
+    /// A valid address of a kind the caller doesn't accept
+    KindNotAllowed(AddressKind),
 }
 
 impl fmt::Display for AddressError {
@@ -24,6 +31,10 @@ impl fmt::Display for AddressError {
             AddressError::InvalidBase58Check(e) => write!(f, "invalid base58check: {e}"),
             AddressError::InvalidLength(n) => write!(f, "invalid payload length: {n}, expected 21"),
             AddressError::UnknownVersionByte(b) => write!(f, "unknown version byte: 0x{b:02x}"),
+            AddressError::WrongLength(n) => write!(f, "invalid hash length: {n}, expected 20"),
+            AddressError::InvalidWif(e) => write!(f, "invalid WIF: {e}"),
+            AddressError::WrongNetwork { expected, found } => write!(f, "expected a {expected} address, got a {found} one"),
+            AddressError::KindNotAllowed(kind) => write!(f, "{kind:?} addresses are not accepted here"),
         }
     }
 }
@@ -35,6 +46,7 @@ impl std::error::Error for AddressError {}
 /// Dogecoin addresses use different prefixes based on network:
 /// - Testnet P2PKH: 'n' or 'm' (version byte 0x71)
 /// - Mainnet P2PKH: 'D' (version byte 0x1E)
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DogeAddress {
     pub payload: Vec<u8>,
     pub network: Network,
@@ -61,6 +73,25 @@ impl DogeAddress {
         Self { payload, network }
     }
 
+    /// Import a WIF private key and return its P2PKH address on the WIF's network.
+    ///
+    /// Respects the WIF compression flag: uncompressed keys hash the 65-byte pubkey,
+    /// which yields a different address than the compressed form.
+    pub fn from_wif(wif: &str) -> Result<(Self, Network), AddressError> {
+        let (secret_key, network, compressed) = wif::decode_wif(wif).map_err(AddressError::InvalidWif)?;
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+
+        let address = if compressed {
+            Self::from_pubkey(&public_key, network)
+        } else {
+            let sha_hash = sha256::Hash::hash(&public_key.serialize_uncompressed());
+            let ripemd_hash = ripemd160::Hash::hash(sha_hash.as_byte_array());
+            Self::from_pubkey_hash(ripemd_hash.as_byte_array(), network)
+        };
+        Ok((address, network))
+    }
+
     /// Create a P2PKH address from a 20-byte pubkey hash
     pub fn from_pubkey_hash(pubkey_hash20: &[u8; 20], network: Network) -> Self {
         let mut payload = Vec::with_capacity(21);
@@ -69,6 +100,12 @@ impl DogeAddress {
         Self { payload, network }
     }
 
+    /// Create a P2PKH address from a pubkey hash of unchecked length (e.g. sliced out of a script)
+    pub fn from_hash_slice(hash: &[u8], network: Network) -> Result<Self, AddressError> {
+        let hash20: &[u8; 20] = hash.try_into().map_err(|_| AddressError::WrongLength(hash.len()))?;
+        Ok(Self::from_pubkey_hash(hash20, network))
+    }
+
     /// Create a P2SH address from a 20-byte script hash (HASH160(redeem_script))
     pub fn from_script_hash(script_hash20: &[u8; 20], network: Network) -> Self {
         let mut payload = Vec::with_capacity(21);
@@ -112,6 +149,14 @@ impl DogeAddress {
         }
     }
 
+    /// The scriptPubKey that pays to this address (P2PKH or P2SH depending on kind)
+    pub fn script_pubkey(&self) -> bitcoin::ScriptBuf {
+        match self.kind() {
+            AddressKind::P2pkh => crate::script::p2pkh_script_pubkey(self.hash160()),
+            AddressKind::P2sh => crate::script::p2sh_script_pubkey_from_hash(self.hash160()),
+        }
+    }
+
     /// Return the 20-byte HASH160 embedded in the address (pubkey-hash for P2PKH, script-hash for P2SH).
     pub fn hash160(&self) -> &[u8] {
         &self.payload[1..21]
@@ -132,22 +177,55 @@ impl DogeAddress {
         // [0] is header, [1..21] is hash
         &self.payload[1..21]
     }
+}
+
+impl FromStr for DogeAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_base58(s)
+    }
+}
+
+impl fmt::Display for DogeAddress {
+    /// Base58Check encoding of the versioned payload (the checksum is appended by `encode_check`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&base58::encode_check(&self.payload))
+    }
+}
 
-    /// Return the Base58Check encoded string
-    pub fn to_string(&self) -> String {
-        // Use bitcoin's internal base58::encode_check if available, or manual simple encode
-        // Since we want to use the library's primitives:
-        // bitcoin::base58::encode_check takes (data) where data includes the prefix? 
-        // usage: base58::encode_check(payload) usually does checksumming.
-        // Let's check if we construct the full payload + checksum manually or use a helper.
-        // bitcoin::base58::check_encode_slice(self.payload) 
-        
-        // Note: The `payload` field in our struct ALREADY includes the version byte (0x71).
-        // Standard Base58Check is: [Version][Payload][Checksum]
-        // `bitcoin::base58::check_encode_slice` usually takes the versioned payload and appends checksum.
-        
-        base58::encode_check(&self.payload)
+/// Validate user input as an address on `network` of one of `allowed_kinds`.
+///
+/// Checks, in order: Base58Check encoding and checksum, a known version byte, the network,
+/// and the kind, so form validation can report the first precise problem.
+pub fn validate_for(s: &str, network: Network, allowed_kinds: &[AddressKind]) -> Result<DogeAddress, AddressError> {
+    let address = DogeAddress::from_base58(s.trim())?;
+    if address.network != network {
+        return Err(AddressError::WrongNetwork { expected: network, found: address.network });
     }
+    if !allowed_kinds.contains(&address.kind()) {
+        return Err(AddressError::KindNotAllowed(address.kind()));
+    }
+    Ok(address)
+}
+
+/// JSON request array for Dogecoin Core's `importmulti`, watching every address in one call.
+///
+/// `timestamp` is the earliest time (Unix seconds) the addresses may have been used, where
+/// the node starts its rescan; `None` sends `"now"` and skips the rescan for fresh addresses.
+pub fn to_importmulti_json(addresses: &[DogeAddress], timestamp: Option<u64>) -> String {
+    let timestamp = timestamp.map_or_else(|| serde_json::json!("now"), |t| serde_json::json!(t));
+    let requests: Vec<serde_json::Value> = addresses
+        .iter()
+        .map(|address| {
+            serde_json::json!({
+                "scriptPubKey": { "address": address.to_string() },
+                "timestamp": timestamp,
+                "watchonly": true,
+            })
+        })
+        .collect();
+    serde_json::Value::Array(requests).to_string()
 }
 
 #[cfg(test)]
@@ -205,6 +283,75 @@ mod tests {
         assert_eq!(parsed.payload, address.payload);
     }
 
+    #[test]
+    fn test_from_hash_slice() {
+        let hash = [0x11u8; 20];
+        let a = DogeAddress::from_hash_slice(&hash, Network::Mainnet).unwrap();
+        assert_eq!(a, DogeAddress::from_pubkey_hash(&hash, Network::Mainnet));
+
+        assert!(matches!(DogeAddress::from_hash_slice(&[0u8; 19], Network::Mainnet), Err(AddressError::WrongLength(19))));
+        assert!(matches!(DogeAddress::from_hash_slice(&[0u8; 32], Network::Mainnet), Err(AddressError::WrongLength(32))));
+    }
+
+    #[test]
+    fn test_from_wif_known_mainnet() {
+        let (compressed, network) = DogeAddress::from_wif("QNcdLVw8fHkixm6NNyN6nVwxKek4u7qrioRbQmjxac5TVoTtZuot").unwrap();
+        assert_eq!(network, Network::Mainnet);
+        assert_eq!(compressed.to_string(), "DFpN6QqFfUm3gKNaxN6tNcab1FArL9cZLE");
+
+        let (uncompressed, _) = DogeAddress::from_wif("6J8csdv3eDrnJcpSEb4shfjMh2JTiG9MKzC1Yfge4Y4GyUsjdM6").unwrap();
+        assert_eq!(uncompressed.to_string(), "DJRU7MLhcPwCTNRZ4e8gJzDebtG1H5M7pc");
+
+        let (testnet, network) = DogeAddress::from_wif("cejxntqoC3o8qiC8HG8DrwoNyiRDBrMCEU8QrUVpLKdXsGy8LpTM").unwrap();
+        assert_eq!(network, Network::Testnet);
+        assert_eq!(testnet.to_string(), "nesRpRaAbTDmZHwmzBkLd2AtF7Z9L9z5S2");
+
+        assert!(matches!(DogeAddress::from_wif("garbage"), Err(AddressError::InvalidWif(_))));
+    }
+
+    #[test]
+    fn test_validate_for() {
+        let p2pkh = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet).to_string();
+        let p2sh = DogeAddress::from_script_hash(&[0x22; 20], Network::Testnet).to_string();
+        let only_p2pkh = [AddressKind::P2pkh];
+
+        assert_eq!(validate_for(&p2pkh, Network::Testnet, &only_p2pkh).unwrap().to_string(), p2pkh);
+        assert!(matches!(
+            validate_for(&p2sh, Network::Testnet, &only_p2pkh),
+            Err(AddressError::KindNotAllowed(AddressKind::P2sh))
+        ));
+        assert!(validate_for(&p2sh, Network::Testnet, &[AddressKind::P2pkh, AddressKind::P2sh]).is_ok());
+        assert!(matches!(
+            validate_for(&p2pkh, Network::Mainnet, &only_p2pkh),
+            Err(AddressError::WrongNetwork { expected: Network::Mainnet, found: Network::Testnet })
+        ));
+
+        // One changed character breaks the checksum
+        let mut typo = p2pkh.clone();
+        let last = typo.pop().unwrap();
+        typo.push(if last == 'z' { 'y' } else { 'z' });
+        assert!(matches!(validate_for(&typo, Network::Testnet, &only_p2pkh), Err(AddressError::InvalidBase58Check(_))));
+    }
+
+    #[test]
+    fn test_to_importmulti_json() {
+        let p2pkh = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        let p2sh = DogeAddress::from_script_hash(&[0x22; 20], Network::Mainnet);
+
+        let json: serde_json::Value = serde_json::from_str(&to_importmulti_json(&[p2pkh.clone(), p2sh.clone()], Some(1_700_000_000))).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "scriptPubKey": { "address": p2pkh.to_string() }, "timestamp": 1_700_000_000, "watchonly": true },
+                { "scriptPubKey": { "address": p2sh.to_string() }, "timestamp": 1_700_000_000, "watchonly": true }
+            ])
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&to_importmulti_json(&[p2pkh], None)).unwrap();
+        assert_eq!(json[0]["timestamp"], "now");
+        assert_eq!(to_importmulti_json(&[], None), "[]");
+    }
+
     #[test]
     fn test_p2sh_prefix_bytes() {
         let hash = [0x11u8; 20];
@@ -0,0 +1,122 @@
+//! DOGE amounts with exact 8-decimal (satoshi) precision.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Satoshis per DOGE
+pub const SATS_PER_DOGE: u64 = 100_000_000;
+
+const DECIMALS: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AmountError {
+    InvalidFormat(String),
+    /// More fractional digits than a satoshi can represent
+    TooPrecise(String),
+    Overflow(String),
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::InvalidFormat(s) => write!(f, "invalid DOGE amount: {s}"),
+            AmountError::TooPrecise(s) => write!(f, "amount has more than {DECIMALS} decimal places: {s}"),
+            AmountError::Overflow(s) => write!(f, "amount out of range: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+/// An amount in satoshis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DogeAmount(u64);
+
+impl DogeAmount {
+    pub const ZERO: DogeAmount = DogeAmount(0);
+
+    pub fn from_sat(sats: u64) -> Self {
+        DogeAmount(sats)
+    }
+
+    pub fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    /// Parse a decimal DOGE string such as `"50"` or `"0.12345678"`.
+    ///
+    /// Inputs with more than 8 fractional digits are rejected with
+    /// `AmountError::TooPrecise` instead of being truncated.
+    pub fn from_doge_str(s: &str) -> Result<Self, AmountError> {
+        let invalid = || AmountError::InvalidFormat(s.to_string());
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return Err(invalid());
+        }
+        if !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if frac.len() > DECIMALS {
+            return Err(AmountError::TooPrecise(s.to_string()));
+        }
+
+        let overflow = || AmountError::Overflow(s.to_string());
+        let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| overflow())? };
+        let frac: u64 = format!("{frac:0<8}").parse().map_err(|_| invalid())?;
+        whole
+            .checked_mul(SATS_PER_DOGE)
+            .and_then(|w| w.checked_add(frac))
+            .map(DogeAmount)
+            .ok_or_else(overflow)
+    }
+}
+
+impl FromStr for DogeAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DogeAmount::from_doge_str(s)
+    }
+}
+
+/// Formats as DOGE with trailing zeros trimmed, e.g. `50` or `0.1`
+impl fmt::Display for DogeAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / SATS_PER_DOGE;
+        let frac = self.0 % SATS_PER_DOGE;
+        if frac == 0 {
+            write!(f, "{whole}")
+        } else {
+            write!(f, "{whole}.{}", format!("{frac:08}").trim_end_matches('0'))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_doge_str() {
+        assert_eq!(DogeAmount::from_doge_str("50").unwrap().to_sat(), 5_000_000_000);
+        assert_eq!(DogeAmount::from_doge_str("0.12345678").unwrap().to_sat(), 12_345_678);
+        assert_eq!(DogeAmount::from_doge_str(".5").unwrap().to_sat(), 50_000_000);
+        assert_eq!(DogeAmount::from_doge_str("1.").unwrap().to_sat(), 100_000_000);
+
+        assert_eq!(
+            DogeAmount::from_doge_str("0.123456789"),
+            Err(AmountError::TooPrecise("0.123456789".to_string()))
+        );
+        assert!(matches!(DogeAmount::from_doge_str("-1"), Err(AmountError::InvalidFormat(_))));
+        assert!(matches!(DogeAmount::from_doge_str("."), Err(AmountError::InvalidFormat(_))));
+        assert!(matches!(DogeAmount::from_doge_str("1e3"), Err(AmountError::InvalidFormat(_))));
+        assert!(matches!(DogeAmount::from_doge_str("999999999999"), Err(AmountError::Overflow(_))));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for s in ["0", "50", "0.1", "1.00000001"] {
+            assert_eq!(DogeAmount::from_doge_str(s).unwrap().to_string(), s);
+        }
+    }
+}
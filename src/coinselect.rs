@@ -0,0 +1,477 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use bitcoin::{OutPoint, ScriptBuf, Txid};
+
+use crate::address::DogeAddress;
+use crate::explorer::ExplorerUtxo;
+use crate::network::{Network, MAX_MONEY_SATS};
+use crate::script;
+use crate::transaction::{DUST_LIMIT, P2PKH_OUTPUT_SIZE, TX_OVERHEAD_SIZE};
+
+#[derive(Debug)]
+pub enum CoinSelectError {
+    InsufficientFunds { needed: u64, available: u64 },
+    /// The amounts (plus fee) overflow or exceed the money supply
+    AmountOverflow,
+}
+
+impl fmt::Display for CoinSelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoinSelectError::InsufficientFunds { needed, available } => {
+                write!(f, "insufficient funds: need {needed} sats, {available} sats spendable")
+            }
+            CoinSelectError::AmountOverflow => write!(f, "amount overflow"),
+        }
+    }
+}
+
+impl std::error::Error for CoinSelectError {}
+
+/// Returns true if the UTXO is a coinbase output that hasn't reached the network's maturity depth.
+pub fn is_immature_coinbase(utxo: &ExplorerUtxo, network: Network) -> bool {
+    utxo.is_coinbase && utxo.confirmations < network.coinbase_maturity()
+}
+
+/// Knobs for coin selection
+#[derive(Debug, Clone, Default)]
+pub struct SelectionOptions {
+    /// Allow coinbase outputs below the maturity depth (default false; consensus rejects them)
+    pub include_immature_coinbase: bool,
+    /// Outpoints earmarked for something else (a pending multisig, a fidelity bond...);
+    /// selection never spends them
+    pub reserved: HashSet<OutPoint>,
+    /// Fee rate in sat/byte for effective-value selection (0 selects by raw value).
+    ///
+    /// When set, each UTXO counts for `effective_value` instead of its face value and UTXOs
+    /// that cost at least as much to spend as they hold are left out. The target should
+    /// then exclude the fee for the inputs themselves, since each UTXO pays its own.
+    pub fee_rate: u64,
+}
+
+impl SelectionOptions {
+    /// Keep `outpoint` out of selection. Returns false if it was already reserved.
+    pub fn reserve(&mut self, outpoint: OutPoint) -> bool {
+        self.reserved.insert(outpoint)
+    }
+
+    /// Make `outpoint` selectable again. Returns false if it wasn't reserved.
+    pub fn unreserve(&mut self, outpoint: &OutPoint) -> bool {
+        self.reserved.remove(outpoint)
+    }
+
+    fn is_reserved(&self, utxo: &ExplorerUtxo) -> bool {
+        !self.reserved.is_empty()
+            && Txid::from_str(&utxo.txid).is_ok_and(|txid| self.reserved.contains(&OutPoint::new(txid, utxo.vout)))
+    }
+}
+
+/// A UTXO's value minus the fee for the input that spends it at `fee_rate` sat/byte.
+///
+/// Input size follows the UTXO's script type. Negative when the UTXO costs more to spend
+/// than it holds; saturates instead of overflowing. `None` if the script isn't valid hex,
+/// since then neither its type nor its input size is known.
+pub fn effective_value(utxo: &ExplorerUtxo, fee_rate: u64) -> Option<i64> {
+    let spk = ScriptBuf::from_bytes(hex::decode(&utxo.script_hex).ok()?);
+    let spend_fee = (script::estimated_input_size(&script::classify(&spk)) as u64).saturating_mul(fee_rate);
+    let value = i64::try_from(utxo.value_satoshis).unwrap_or(i64::MAX);
+    Some(value.saturating_sub(i64::try_from(spend_fee).unwrap_or(i64::MAX)))
+}
+
+/// Result of coin selection
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub selected: Vec<ExplorerUtxo>,
+    /// Immature coinbase outputs that were skipped, e.g. to show "X DOGE maturing"
+    pub immature: Vec<ExplorerUtxo>,
+}
+
+impl Selection {
+    pub fn selected_total(&self) -> u64 {
+        self.selected.iter().map(|u| u.value_satoshis).sum()
+    }
+
+    pub fn immature_total(&self) -> u64 {
+        self.immature.iter().map(|u| u.value_satoshis).sum()
+    }
+}
+
+/// Largest-first coin selection.
+///
+/// Picks the biggest UTXOs until `target_sats` is covered. Immature coinbase outputs
+/// are skipped since consensus rejects spending them.
+pub fn select_largest_first(
+    utxos: &[ExplorerUtxo],
+    target_sats: u64,
+    network: Network,
+) -> Result<Vec<ExplorerUtxo>, CoinSelectError> {
+    select_with_options(utxos, target_sats, network, &SelectionOptions::default()).map(|s| s.selected)
+}
+
+/// Largest-first coin selection with options, reporting skipped immature coinbase outputs separately.
+///
+/// UTXOs with an unknown or malformed prevout script are never selected since they couldn't be signed,
+/// and reserved outpoints are never selected at all. With `options.fee_rate` set, UTXOs are
+/// ranked and counted by effective value, and `InsufficientFunds::available` reports the
+/// total effective value.
+pub fn select_with_options(
+    utxos: &[ExplorerUtxo],
+    target_sats: u64,
+    network: Network,
+    options: &SelectionOptions,
+) -> Result<Selection, CoinSelectError> {
+    let mut candidates = Vec::new();
+    let mut immature = Vec::new();
+    for u in utxos.iter().filter(|u| !u.script_hex.is_empty() && !options.is_reserved(u)) {
+        if !options.include_immature_coinbase && is_immature_coinbase(u, network) {
+            immature.push(u.clone());
+        } else {
+            match effective_value(u, options.fee_rate) {
+                Some(value) if value > 0 => candidates.push((u, value as u64)),
+                _ => {}
+            }
+        }
+    }
+    candidates.sort_by_key(|(_, value)| std::cmp::Reverse(*value));
+
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    for (u, value) in candidates {
+        if total >= target_sats {
+            break;
+        }
+        total = total.saturating_add(value);
+        selected.push(u.clone());
+    }
+
+    if total < target_sats {
+        return Err(CoinSelectError::InsufficientFunds { needed: target_sats, available: total });
+    }
+
+    Ok(Selection { selected, immature })
+}
+
+/// How many inputs largest-first selection needs to cover `target_sats` plus the fee
+/// for that many inputs at `fee_rate` sat/byte, or `None` if the UTXOs can't cover it.
+///
+/// The fee assumes a recipient and a change output, both P2PKH. Each extra input
+/// raises the fee, so the answer accounts for it input by input. Immature coinbase
+/// outputs on `network` are skipped.
+pub fn min_inputs_for(utxos: &[ExplorerUtxo], target_sats: u64, fee_rate: u64, network: Network) -> Option<usize> {
+    let mut candidates: Vec<&ExplorerUtxo> = utxos
+        .iter()
+        .filter(|u| !u.script_hex.is_empty() && !is_immature_coinbase(u, network))
+        .collect();
+    candidates.sort_by_key(|u| std::cmp::Reverse(u.value_satoshis));
+
+    let mut total = 0u64;
+    let mut size = TX_OVERHEAD_SIZE + 2 * P2PKH_OUTPUT_SIZE;
+    for (i, u) in candidates.iter().enumerate() {
+        let spk = ScriptBuf::from_bytes(hex::decode(&u.script_hex).unwrap_or_default());
+        size += script::estimated_input_size(&script::classify(&spk));
+        total = total.saturating_add(u.value_satoshis);
+        if total >= target_sats.saturating_add(size as u64 * fee_rate) {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// Split the total UTXO value into `(spendable, dust)` at `fee_rate` sat/byte.
+///
+/// A UTXO is dust when the fee for the input that spends it is at least its value,
+/// so including it would lose money. Input size follows the UTXO's script type; a UTXO
+/// whose script isn't valid hex can't be spent and counts as dust.
+pub fn spendable_balance(utxos: &[ExplorerUtxo], fee_rate: u64) -> (u64, u64) {
+    utxos.iter().fold((0, 0), |(spendable, dust), u| {
+        if effective_value(u, fee_rate).is_some_and(|v| v > 0) {
+            (spendable + u.value_satoshis, dust)
+        } else {
+            (spendable, dust + u.value_satoshis)
+        }
+    })
+}
+
+/// What happens to the leftover once a selection covers the target and fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeDecision {
+    /// Leftover is worth a change output of this amount
+    WithChange(u64),
+    /// Leftover is below the dust threshold and is paid as extra fee
+    NoChangeAbsorbedToFee(u64),
+    InsufficientFunds,
+}
+
+/// Decide whether spending `selected_total` on `target + fee` leaves a change output.
+pub fn will_produce_change(selected_total: u64, target: u64, fee: u64, dust_threshold: u64) -> ChangeDecision {
+    match selected_total.checked_sub(target.saturating_add(fee)) {
+        None => ChangeDecision::InsufficientFunds,
+        Some(leftover) if leftover < dust_threshold => ChangeDecision::NoChangeAbsorbedToFee(leftover),
+        Some(leftover) => ChangeDecision::WithChange(leftover),
+    }
+}
+
+/// Dry-run cost of a payment, in satoshis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// Sum of the recipient amounts
+    pub sent: u64,
+    pub fee: u64,
+    /// `sent + fee`
+    pub total: u64,
+    /// Change returned to the wallet (0 when dust is absorbed into the fee)
+    pub change: u64,
+}
+
+/// Preview what paying `recipients` from `utxos` at `fee_rate` sat/byte would cost.
+///
+/// Runs largest-first selection and the fee calculation without building or signing,
+/// e.g. for a "50 DOGE + 0.01 DOGE fee = 50.01 DOGE" confirmation screen. Input sizes
+/// follow each UTXO's script type; change below `DUST_LIMIT` is counted as fee.
+pub fn estimate_total_cost(
+    recipients: &[(DogeAddress, u64)],
+    utxos: &[ExplorerUtxo],
+    fee_rate: u64,
+    network: Network,
+) -> Result<CostEstimate, CoinSelectError> {
+    plan_payment(recipients, utxos, fee_rate, network).map(|(_, estimate)| estimate)
+}
+
+/// Select UTXOs for paying `recipients` and work out the fee and change.
+///
+/// Selection runs on effective value, so each UTXO pays for its own input and UTXOs not
+/// worth spending are skipped; the target is the amounts plus the fee for the rest of the
+/// transaction, assuming a P2PKH change output. Returns the chosen UTXOs with the estimate.
+pub fn plan_payment(
+    recipients: &[(DogeAddress, u64)],
+    utxos: &[ExplorerUtxo],
+    fee_rate: u64,
+    network: Network,
+) -> Result<(Vec<ExplorerUtxo>, CostEstimate), CoinSelectError> {
+    let sent = recipients
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount).filter(|t| *t <= MAX_MONEY_SATS))
+        .ok_or(CoinSelectError::AmountOverflow)?;
+    let fee_for = |size: usize| (size as u64).checked_mul(fee_rate).ok_or(CoinSelectError::AmountOverflow);
+    let outputs_size: usize = recipients
+        .iter()
+        .map(|(address, _)| 9 + address.script_pubkey().len())
+        .sum();
+
+    let base_size = TX_OVERHEAD_SIZE + outputs_size + P2PKH_OUTPUT_SIZE;
+    let options = SelectionOptions { fee_rate, ..Default::default() };
+    let target = sent.checked_add(fee_for(base_size)?).ok_or(CoinSelectError::AmountOverflow)?;
+    let selection = select_with_options(utxos, target, network, &options)?;
+    let inputs_size: usize = selection
+        .selected
+        .iter()
+        .map(|u| {
+            let spk = ScriptBuf::from_bytes(hex::decode(&u.script_hex).unwrap_or_default());
+            script::estimated_input_size(&script::classify(&spk))
+        })
+        .sum();
+    let needed_fee = fee_for(base_size + inputs_size)?;
+
+    let (fee, change) = match will_produce_change(selection.selected_total(), sent, needed_fee, DUST_LIMIT) {
+        ChangeDecision::WithChange(change) => (needed_fee, change),
+        ChangeDecision::NoChangeAbsorbedToFee(extra) => (needed_fee + extra, 0),
+        // Effective values already cover every input's fee
+        ChangeDecision::InsufficientFunds => {
+            return Err(CoinSelectError::InsufficientFunds {
+                needed: sent.saturating_add(needed_fee),
+                available: selection.selected_total(),
+            })
+        }
+    };
+    let total = sent.checked_add(fee).ok_or(CoinSelectError::AmountOverflow)?;
+    Ok((selection.selected, CostEstimate { sent, fee, total, change }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utxo(vout: u32, value_satoshis: u64, confirmations: u64, is_coinbase: bool) -> ExplorerUtxo {
+        ExplorerUtxo {
+            txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+            vout,
+            value_satoshis,
+            script_hex: "76a914111111111111111111111111111111111111111188ac".to_string(),
+            confirmations,
+            is_coinbase,
+        }
+    }
+
+    #[test]
+    fn test_select_largest_first() {
+        let utxos = vec![utxo(0, 1_000, 10, false), utxo(1, 5_000, 10, false), utxo(2, 3_000, 10, false)];
+        let selected = select_largest_first(&utxos, 7_000, Network::Testnet).unwrap();
+        assert_eq!(selected.iter().map(|u| u.vout).collect::<Vec<_>>(), vec![1, 2]);
+
+        assert!(matches!(
+            select_largest_first(&utxos, 10_000, Network::Testnet),
+            Err(CoinSelectError::InsufficientFunds { needed: 10_000, available: 9_000 })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_script_skipped() {
+        let mut unknown = utxo(0, 1_000_000, 10, false);
+        unknown.script_hex.clear();
+        let utxos = vec![unknown, utxo(1, 2_000, 10, false)];
+        let selected = select_largest_first(&utxos, 1_000, Network::Testnet).unwrap();
+        assert_eq!(selected[0].vout, 1);
+    }
+
+    #[test]
+    fn test_immature_coinbase_excluded() {
+        let utxos = vec![utxo(0, 1_000_000, 10, true), utxo(1, 2_000, 10, false)];
+        let selected = select_largest_first(&utxos, 1_000, Network::Mainnet).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].vout, 1);
+
+        assert!(select_largest_first(&utxos, 10_000, Network::Mainnet).is_err());
+
+        let mature = vec![utxo(0, 1_000_000, 240, true)];
+        assert_eq!(select_largest_first(&mature, 10_000, Network::Mainnet).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_immature_coinbase_reported_separately() {
+        let utxos = vec![utxo(0, 1_000_000, 10, true), utxo(1, 2_000, 10, false)];
+
+        let selection = select_with_options(&utxos, 1_000, Network::Mainnet, &SelectionOptions::default()).unwrap();
+        assert_eq!(selection.selected_total(), 2_000);
+        assert_eq!(selection.immature_total(), 1_000_000);
+
+        let options = SelectionOptions { include_immature_coinbase: true, ..Default::default() };
+        let selection = select_with_options(&utxos, 10_000, Network::Mainnet, &options).unwrap();
+        assert_eq!(selection.selected[0].vout, 0);
+        assert!(selection.immature.is_empty());
+    }
+
+    #[test]
+    fn test_reserved_outpoints_are_skipped() {
+        let utxos = vec![utxo(0, 5_000, 10, false), utxo(1, 3_000, 10, false), utxo(2, 1_000, 10, false)];
+        let txid = Txid::from_str(&utxos[0].txid).unwrap();
+
+        let mut options = SelectionOptions::default();
+        assert!(options.reserve(OutPoint::new(txid, 0)));
+        assert!(!options.reserve(OutPoint::new(txid, 0)));
+        let selection = select_with_options(&utxos, 4_000, Network::Mainnet, &options).unwrap();
+        assert_eq!(selection.selected.iter().map(|u| u.vout).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(matches!(
+            select_with_options(&utxos, 5_000, Network::Mainnet, &options),
+            Err(CoinSelectError::InsufficientFunds { available: 4_000, .. })
+        ));
+
+        assert!(options.unreserve(&OutPoint::new(txid, 0)));
+        assert!(!options.unreserve(&OutPoint::new(txid, 0)));
+        let selection = select_with_options(&utxos, 4_000, Network::Mainnet, &options).unwrap();
+        assert_eq!(selection.selected[0].vout, 0);
+    }
+
+    #[test]
+    fn test_effective_value_selection() {
+        // A P2PKH input costs 148_000 sats at 1_000 sat/byte
+        assert_eq!(effective_value(&utxo(0, 1_000_000, 10, false), 1_000), Some(852_000));
+        assert_eq!(effective_value(&utxo(0, 100_000, 10, false), 1_000), Some(-48_000));
+
+        let utxos = vec![utxo(0, 100_000, 10, false), utxo(1, 1_000_000, 10, false), utxo(2, 900_000, 10, false)];
+        let options = SelectionOptions { fee_rate: 1_000, ..Default::default() };
+        let selection = select_with_options(&utxos, 1_500_000, Network::Mainnet, &options).unwrap();
+        assert_eq!(selection.selected.iter().map(|u| u.vout).collect::<Vec<_>>(), vec![1, 2]);
+
+        // By raw value all three cover 2_000_000; by effective value the 100_000 one is excluded
+        assert!(select_with_options(&utxos, 2_000_000, Network::Mainnet, &SelectionOptions::default()).is_ok());
+        assert!(matches!(
+            select_with_options(&utxos, 2_000_000, Network::Mainnet, &options),
+            Err(CoinSelectError::InsufficientFunds { available: 1_604_000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_effective_value_edge_cases() {
+        assert_eq!(effective_value(&utxo(0, u64::MAX, 10, false), 0), Some(i64::MAX));
+        assert_eq!(effective_value(&utxo(0, 1_000, 10, false), u64::MAX), Some(1_000 - i64::MAX));
+
+        let mut malformed = utxo(1, 5_000_000, 10, false);
+        malformed.script_hex = "zz".to_string();
+        assert_eq!(effective_value(&malformed, 1_000), None);
+        let utxos = vec![utxo(0, 1_000_000, 10, false), malformed];
+        let options = SelectionOptions { fee_rate: 1_000, ..Default::default() };
+        assert!(matches!(
+            select_with_options(&utxos, 1_000_000, Network::Mainnet, &options),
+            Err(CoinSelectError::InsufficientFunds { available: 852_000, .. })
+        ));
+        assert_eq!(spendable_balance(&utxos, 1_000), (1_000_000, 5_000_000));
+    }
+
+    #[test]
+    fn test_min_inputs_for() {
+        let utxos = vec![utxo(0, 100_000_000, 10, false), utxo(1, 300_000_000, 10, false), utxo(2, 200_000_000, 10, false)];
+        assert_eq!(min_inputs_for(&utxos, 250_000_000, 1_000, Network::Mainnet), Some(1));
+        assert_eq!(min_inputs_for(&utxos, 400_000_000, 1_000, Network::Mainnet), Some(2));
+        // 500_000_000 exactly is covered by two inputs only before fees
+        assert_eq!(min_inputs_for(&utxos, 500_000_000, 1_000, Network::Mainnet), Some(3));
+        assert_eq!(min_inputs_for(&utxos, 600_000_000, 1_000, Network::Mainnet), None);
+
+        // An immature coinbase output can't be counted on
+        let with_coinbase = vec![utxo(0, 900_000_000, 10, true), utxo(1, 300_000_000, 10, false)];
+        assert_eq!(min_inputs_for(&with_coinbase, 250_000_000, 1_000, Network::Testnet), Some(1));
+        assert_eq!(min_inputs_for(&with_coinbase, 400_000_000, 1_000, Network::Testnet), None);
+    }
+
+    #[test]
+    fn test_spendable_balance() {
+        // A P2PKH input costs 148 bytes, i.e. 148_000 sats at 1_000 sat/byte
+        let utxos = vec![utxo(0, 5_000_000_000, 10, false), utxo(1, 148_000, 10, false), utxo(2, 100_000, 10, false)];
+        assert_eq!(spendable_balance(&utxos, 1_000), (5_000_000_000, 248_000));
+        assert_eq!(spendable_balance(&utxos, 1), (5_000_248_000, 0));
+        assert_eq!(spendable_balance(&[], 1_000), (0, 0));
+    }
+
+    #[test]
+    fn test_will_produce_change() {
+        assert_eq!(will_produce_change(10_000_000, 5_000_000, 100_000, DUST_LIMIT), ChangeDecision::WithChange(4_900_000));
+        assert_eq!(
+            will_produce_change(5_600_000, 5_000_000, 100_000, DUST_LIMIT),
+            ChangeDecision::NoChangeAbsorbedToFee(500_000)
+        );
+        assert_eq!(will_produce_change(5_100_000, 5_000_000, 100_000, DUST_LIMIT), ChangeDecision::NoChangeAbsorbedToFee(0));
+        assert_eq!(will_produce_change(5_000_000, 5_000_000, 100_000, DUST_LIMIT), ChangeDecision::InsufficientFunds);
+    }
+
+    #[test]
+    fn test_estimate_total_cost() {
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Mainnet);
+        let recipients = vec![(to, 5_000_000_000)];
+        let utxos = vec![utxo(0, 3_000_000_000, 10, false), utxo(1, 4_000_000_000, 10, false)];
+
+        // 2 P2PKH inputs, recipient + change outputs
+        let estimate = estimate_total_cost(&recipients, &utxos, 1_000, Network::Mainnet).unwrap();
+        let fee = (TX_OVERHEAD_SIZE + 2 * 148 + 2 * P2PKH_OUTPUT_SIZE) as u64 * 1_000;
+        assert_eq!(estimate, CostEstimate { sent: 5_000_000_000, fee, total: 5_000_000_000 + fee, change: 2_000_000_000 - fee });
+
+        // Change below the dust limit is absorbed into the fee
+        let exact = vec![utxo(0, 5_000_500_000, 10, false)];
+        let estimate = estimate_total_cost(&recipients, &exact, 1_000, Network::Mainnet).unwrap();
+        assert_eq!(estimate.change, 0);
+        assert_eq!(estimate.fee, 500_000);
+        assert_eq!(estimate.total, 5_000_500_000);
+
+        assert!(matches!(
+            estimate_total_cost(&recipients, &utxos[..1], 1_000, Network::Mainnet),
+            Err(CoinSelectError::InsufficientFunds { .. })
+        ));
+
+        // Recipient amounts that overflow, or merely pass the money supply, are rejected
+        let huge = vec![(recipients[0].0.clone(), u64::MAX), (recipients[0].0.clone(), 1)];
+        assert!(matches!(estimate_total_cost(&huge, &utxos, 1_000, Network::Mainnet), Err(CoinSelectError::AmountOverflow)));
+        let over_supply = vec![(recipients[0].0.clone(), MAX_MONEY_SATS), (recipients[0].0.clone(), 1)];
+        assert!(matches!(estimate_total_cost(&over_supply, &utxos, 1_000, Network::Mainnet), Err(CoinSelectError::AmountOverflow)));
+        assert!(matches!(estimate_total_cost(&recipients, &utxos, u64::MAX, Network::Mainnet), Err(CoinSelectError::AmountOverflow)));
+    }
+}
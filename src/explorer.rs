@@ -1,7 +1,10 @@
 use serde::Deserialize;
 use std::error::Error;
 
+use crate::address::DogeAddress;
+use crate::amount::DogeAmount;
 use crate::network::Network;
+use crate::rpc::{DogeRpcClient, RpcError, UtxoInfo, RPC_METHOD_NOT_FOUND};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ExplorerNetwork {
@@ -30,16 +33,58 @@ pub struct ExplorerUtxo {
     pub txid: String,
     pub vout: u32,
     pub value_satoshis: u64,
+    /// Prevout scriptPubKey hex; empty if the explorer omitted it and it couldn't be derived
     pub script_hex: String,
     pub confirmations: u64,
+    /// Whether this output was created by a coinbase transaction (explorers that don't report it leave this `false`).
+    pub is_coinbase: bool,
 }
 
+impl From<ExplorerUtxo> for UtxoInfo {
+    fn from(u: ExplorerUtxo) -> Self {
+        UtxoInfo {
+            txid: u.txid,
+            vout: u.vout,
+            value: u.value_satoshis,
+            script_pubkey: u.script_hex,
+            confirmations: u.confirmations,
+            is_coinbase: u.is_coinbase,
+        }
+    }
+}
+
+impl From<UtxoInfo> for ExplorerUtxo {
+    fn from(u: UtxoInfo) -> Self {
+        ExplorerUtxo {
+            txid: u.txid,
+            vout: u.vout,
+            value_satoshis: u.value,
+            script_hex: u.script_pubkey,
+            confirmations: u.confirmations,
+            is_coinbase: u.is_coinbase,
+        }
+    }
+}
+
+/// An address paired with its balance lookup result
+pub type AddressBalance = (String, Result<u64, Box<dyn Error>>);
+
+/// Maximum number of outputs chain.so returns per `get_tx_unspent` call
+pub const CHAIN_SO_PAGE_SIZE: usize = 100;
+
 /// Chain.so public API client.
 ///
 /// Docs (high-level): https://chain.so/api
 pub struct ChainSoClient {
     base_url: String,
     client: reqwest::blocking::Client,
+    user_agent: String,
+}
+
+impl Default for ChainSoClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ChainSoClient {
@@ -47,6 +92,7 @@ impl ChainSoClient {
         Self {
             base_url: "https://chain.so/api/v2".to_string(),
             client: reqwest::blocking::Client::new(),
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
         }
     }
 
@@ -54,32 +100,122 @@ impl ChainSoClient {
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
             client: reqwest::blocking::Client::new(),
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
         }
     }
 
+    /// Override the User-Agent header sent with every request
+    pub fn with_user_agent(mut self, ua: &str) -> Self {
+        self.user_agent = ua.to_string();
+        self
+    }
+
+    /// All unspent outputs of `address`.
+    ///
+    /// chain.so returns at most `CHAIN_SO_PAGE_SIZE` outputs per call, so full pages are
+    /// followed by another request continuing after the last txid seen.
     pub fn get_tx_unspent(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
         let net = ExplorerNetwork::from_network(network).as_str();
-        let url = format!("{}/get_tx_unspent/{}/{}", self.base_url, net, address);
 
-        let resp: ChainSoEnvelope<ChainSoTxUnspentData> = self.client.get(url).send()?.json()?;
+        // Some responses omit script_hex; the queried address tells us what the scriptPubKey must be
+        let derived_script = address
+            .parse::<DogeAddress>()
+            .ok()
+            .map(|a| hex::encode(a.script_pubkey().as_bytes()));
+
+        let mut utxos: Vec<ExplorerUtxo> = Vec::new();
+        loop {
+            let mut url = format!("{}/get_tx_unspent/{}/{}", self.base_url, net, address);
+            if let Some(last) = utxos.last() {
+                url = format!("{url}/{}", last.txid);
+            }
+
+            let resp: ChainSoEnvelope<ChainSoTxUnspentData> = self
+                .client
+                .get(url)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .send()?
+                .json()?;
+            if resp.status != "success" {
+                return Err(format!("chain.so status: {}", resp.status).into());
+            }
+
+            let page_len = resp.data.txs.len();
+            let seen_before = utxos.len();
+            for u in resp.data.txs {
+                // Outputs of the cursor transaction can be repeated at a page boundary
+                if utxos.iter().any(|seen| seen.txid == u.txid && seen.vout == u.output_no) {
+                    continue;
+                }
+                let value_satoshis = (u.value.parse::<f64>()? * 100_000_000.0) as u64;
+                let confirmations = u.confirmations.unwrap_or(0);
+                utxos.push(ExplorerUtxo {
+                    txid: u.txid,
+                    vout: u.output_no,
+                    value_satoshis,
+                    script_hex: if u.script_hex.is_empty() {
+                        derived_script.clone().unwrap_or_default()
+                    } else {
+                        u.script_hex
+                    },
+                    confirmations,
+                    is_coinbase: false,
+                });
+            }
+
+            // A page with nothing new would repeat the same cursor forever
+            if page_len < CHAIN_SO_PAGE_SIZE || utxos.len() == seen_before {
+                break;
+            }
+        }
+
+        Ok(utxos)
+    }
+
+    /// Whether `address` has ever received coins, via `get_tx_received`
+    pub fn has_received(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>> {
+        let net = ExplorerNetwork::from_network(network).as_str();
+        let url = format!("{}/get_tx_received/{}/{}", self.base_url, net, address);
+
+        let resp: ChainSoEnvelope<ChainSoTxReceivedData> = self
+            .client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .send()?
+            .json()?;
         if resp.status != "success" {
             return Err(format!("chain.so status: {}", resp.status).into());
         }
 
-        let mut utxos = Vec::new();
-        for u in resp.data.txs {
-            let value_satoshis = (u.value.parse::<f64>()? * 100_000_000.0) as u64;
-            let confirmations = u.confirmations.unwrap_or(0);
-            utxos.push(ExplorerUtxo {
-                txid: u.txid,
-                vout: u.output_no,
-                value_satoshis,
-                script_hex: u.script_hex,
-                confirmations,
-            });
+        Ok(!resp.data.txs.is_empty())
+    }
+
+    /// Confirmed balance of `address` in satoshis
+    pub fn get_address_balance(&self, address: &str, network: Network) -> Result<u64, Box<dyn Error>> {
+        let net = ExplorerNetwork::from_network(network).as_str();
+        let url = format!("{}/get_address_balance/{}/{}", self.base_url, net, address);
+
+        let resp: ChainSoEnvelope<ChainSoBalanceData> = self
+            .client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .send()?
+            .json()?;
+        if resp.status != "success" {
+            return Err(format!("chain.so status: {}", resp.status).into());
         }
 
-        Ok(utxos)
+        Ok(DogeAmount::from_doge_str(&resp.data.confirmed_balance)?.to_sat())
+    }
+
+    /// Confirmed balances of several addresses, fetched one after another.
+    ///
+    /// A failed lookup is reported in that address's slot and doesn't stop the rest.
+    pub fn balances(&self, addresses: &[&str], network: Network) -> Vec<AddressBalance> {
+        addresses
+            .iter()
+            .map(|address| (address.to_string(), self.get_address_balance(address, network)))
+            .collect()
     }
 
     pub fn send_tx(&self, tx_hex: &str, network: Network) -> Result<String, Box<dyn Error>> {
@@ -87,7 +223,13 @@ impl ChainSoClient {
         let url = format!("{}/send_tx/{}/", self.base_url, net);
 
         let req = ChainSoSendTxRequest { tx_hex };
-        let resp: ChainSoEnvelope<ChainSoSendTxData> = self.client.post(url).json(&req).send()?.json()?;
+        let resp: ChainSoEnvelope<ChainSoSendTxData> = self
+            .client
+            .post(url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .json(&req)
+            .send()?
+            .json()?;
         if resp.status != "success" {
             return Err(format!("chain.so status: {}", resp.status).into());
         }
@@ -103,6 +245,7 @@ pub struct SoChainV3Client {
     base_url: String,
     api_key: String,
     client: reqwest::blocking::Client,
+    user_agent: String,
 }
 
 impl SoChainV3Client {
@@ -111,7 +254,79 @@ impl SoChainV3Client {
             base_url: "https://chain.so/api/v3".to_string(),
             api_key: api_key.to_string(),
             client: reqwest::blocking::Client::new(),
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+
+    /// Point the client at another v3-compatible endpoint
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Override the User-Agent header sent with every request
+    pub fn with_user_agent(mut self, ua: &str) -> Self {
+        self.user_agent = ua.to_string();
+        self
+    }
+
+    /// All unspent outputs of `address`, following the paged `unspent_outputs` endpoint
+    /// until a page comes back empty.
+    ///
+    /// v3 reports the block an output was mined in rather than always giving confirmations;
+    /// a mined output without a count is treated as having one confirmation.
+    pub fn get_unspent_outputs(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+        let net = ExplorerNetwork::from_network(network).as_str();
+
+        let mut utxos = Vec::new();
+        for page in 1.. {
+            let url = format!("{}/unspent_outputs/{}/{}/{}", self.base_url, net, address, page);
+            let resp: SoChainV3Envelope<SoChainV3UnspentOutputs> = self
+                .client
+                .get(url)
+                .header("API-KEY", &self.api_key)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .send()?
+                .json()?;
+            if resp.status != "success" {
+                return Err(format!("chain.so v3 status: {}", resp.status).into());
+            }
+            if resp.data.outputs.is_empty() {
+                break;
+            }
+
+            for o in resp.data.outputs {
+                utxos.push(ExplorerUtxo {
+                    value_satoshis: DogeAmount::from_doge_str(&o.value)?.to_sat(),
+                    confirmations: o.confirmations.unwrap_or(u64::from(o.block.is_some())),
+                    txid: o.hash,
+                    vout: o.index,
+                    script_hex: o.script,
+                    is_coinbase: false,
+                });
+            }
         }
+
+        Ok(utxos)
+    }
+
+    /// Whether `address` appears in any transaction, via the first page of `transactions`
+    pub fn has_received(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>> {
+        let net = ExplorerNetwork::from_network(network).as_str();
+        let url = format!("{}/transactions/{}/{}/1", self.base_url, net, address);
+
+        let resp: SoChainV3Envelope<SoChainV3Transactions> = self
+            .client
+            .get(url)
+            .header("API-KEY", &self.api_key)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .send()?
+            .json()?;
+        if resp.status != "success" {
+            return Err(format!("chain.so v3 status: {}", resp.status).into());
+        }
+
+        Ok(!resp.data.transactions.is_empty())
     }
 
     pub fn fetch_output(&self, txid: &str, vout: u32, network: Network) -> Result<ExplorerUtxo, Box<dyn Error>> {
@@ -122,6 +337,7 @@ impl SoChainV3Client {
             .client
             .get(url)
             .header("API-KEY", &self.api_key)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
             .send()?
             .json()?;
 
@@ -155,10 +371,124 @@ impl SoChainV3Client {
             value_satoshis,
             script_hex,
             confirmations,
+            is_coinbase: false,
         })
     }
 }
 
+/// Any source of unspent outputs for an address (explorer, node, ...)
+pub trait UtxoProvider {
+    fn get_utxos(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>>;
+
+    /// Whether `address` has ever received coins, including ones since spent
+    fn has_history(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>>;
+}
+
+impl UtxoProvider for ChainSoClient {
+    fn get_utxos(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+        self.get_tx_unspent(address, network)
+    }
+
+    fn has_history(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>> {
+        self.has_received(address, network)
+    }
+}
+
+impl UtxoProvider for SoChainV3Client {
+    fn get_utxos(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+        self.get_unspent_outputs(address, network)
+    }
+
+    fn has_history(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>> {
+        self.has_received(address, network)
+    }
+}
+
+/// Scans the node's UTXO set for `addr(<address>)`. Only nodes that lack `scantxoutset`
+/// (Dogecoin Core 1.14) fall back to `listunspent`, which only sees wallet or watch-only
+/// addresses; any other scan failure is returned. `network` is ignored: the node serves the
+/// chain it runs on.
+///
+/// History of wallet and watch-only addresses comes from `getreceivedbyaddress`. For any other
+/// address the node only sees unspent outputs, so a scan hit counts as history and an empty
+/// scan is an error rather than a guess that the address is fresh.
+impl UtxoProvider for DogeRpcClient {
+    fn get_utxos(&self, address: &str, _network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+        let utxos = match self.scan_txout_set(&[&format!("addr({address})")]) {
+            Ok(utxos) => utxos,
+            Err(e) if e.downcast_ref::<RpcError>().is_some_and(|e| e.code == RPC_METHOD_NOT_FOUND) => {
+                self.list_unspent(&[address])?
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(utxos.into_iter().map(ExplorerUtxo::from).collect())
+    }
+
+    fn has_history(&self, address: &str, network: Network) -> Result<bool, Box<dyn Error>> {
+        if self.is_wallet_address(address)? {
+            return Ok(self.get_received_by_address(address, 0)? > 0);
+        }
+        if !self.get_utxos(address, network)?.is_empty() {
+            return Ok(true);
+        }
+        Err(format!(
+            "node can't tell whether {address} has history: it isn't in the node wallet \
+             (import it watch-only or use an explorer)"
+        )
+        .into())
+    }
+}
+
+/// Every provider in a `UtxoPool` failed; holds each provider's error in order
+#[derive(Debug)]
+pub struct UtxoPoolError(pub Vec<String>);
+
+impl std::fmt::Display for UtxoPoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "all UTXO providers failed: {}", self.0.join("; "))
+    }
+}
+
+impl Error for UtxoPoolError {}
+
+/// Fetches UTXOs from several providers, falling back in order.
+///
+/// The first non-empty result wins. An empty result is only returned when no provider
+/// found anything and at least one succeeded; if all fail, the errors are aggregated.
+#[derive(Default)]
+pub struct UtxoPool {
+    providers: Vec<Box<dyn UtxoProvider>>,
+}
+
+impl UtxoPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: impl UtxoProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    pub fn get_utxos(&self, address: &str, network: Network) -> Result<Vec<ExplorerUtxo>, UtxoPoolError> {
+        let mut errors = Vec::new();
+        let mut any_ok = false;
+        for provider in &self.providers {
+            match provider.get_utxos(address, network) {
+                Ok(utxos) if !utxos.is_empty() => return Ok(utxos),
+                Ok(_) => any_ok = true,
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        if any_ok {
+            Ok(Vec::new())
+        } else {
+            Err(UtxoPoolError(errors))
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct SoChainV3Envelope<T> {
     status: String,
@@ -172,6 +502,29 @@ struct SoChainV3Transaction {
     outputs: Vec<SoChainV3Output>,
 }
 
+#[derive(Debug, Deserialize)]
+struct SoChainV3UnspentOutputs {
+    outputs: Vec<SoChainV3UnspentOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SoChainV3UnspentOutput {
+    hash: String,
+    index: u32,
+    #[serde(default)]
+    script: String,
+    value: String,
+    #[serde(default)]
+    block: Option<u64>,
+    #[serde(default)]
+    confirmations: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SoChainV3Transactions {
+    transactions: Vec<serde::de::IgnoredAny>,
+}
+
 #[derive(Debug, Deserialize)]
 struct SoChainV3Output {
     index: u32,
@@ -202,11 +555,24 @@ struct ChainSoUnspentTx {
     txid: String,
     output_no: u32,
     value: String,
+    #[serde(default)]
     script_hex: String,
     #[serde(default)]
     confirmations: Option<u64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ChainSoTxReceivedData {
+    txs: Vec<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainSoBalanceData {
+    // Absent on failure responses, which are rejected by status first
+    #[serde(default)]
+    confirmed_balance: String,
+}
+
 #[derive(Debug, serde::Serialize)]
 struct ChainSoSendTxRequest<'a> {
     tx_hex: &'a str,
@@ -216,3 +582,284 @@ struct ChainSoSendTxRequest<'a> {
 struct ChainSoSendTxData {
     txid: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::mock_server;
+    use serde_json::json;
+
+    #[test]
+    fn test_chain_so_user_agent() {
+        let body = r#"{"status":"success","data":{"txs":[]}}"#.to_string();
+        let (url, requests) = mock_server(vec![body.clone(), body]);
+
+        ChainSoClient::with_base_url(&url).get_tx_unspent("addr", Network::Testnet).unwrap();
+        let req = requests.recv().unwrap();
+        assert!(req.head.starts_with("GET /get_tx_unspent/DOGETEST/addr "));
+        assert!(req.head.to_ascii_lowercase().contains(&format!("user-agent: {}", crate::DEFAULT_USER_AGENT)));
+
+        ChainSoClient::with_base_url(&url)
+            .with_user_agent("my-app/1.0")
+            .get_tx_unspent("addr", Network::Testnet)
+            .unwrap();
+        assert!(requests.recv().unwrap().head.to_ascii_lowercase().contains("user-agent: my-app/1.0"));
+    }
+
+    #[test]
+    fn test_missing_script_hex_is_derived() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let body = json!({
+            "status": "success",
+            "data": { "txs": [
+                { "txid": "aa", "output_no": 0, "value": "1.5", "confirmations": 3 },
+                { "txid": "bb", "output_no": 1, "value": "2.0", "script_hex": "51", "confirmations": 3 }
+            ]}
+        })
+        .to_string();
+        let (url, _requests) = mock_server(vec![body.clone(), body]);
+
+        let utxos = ChainSoClient::with_base_url(&url).get_tx_unspent(&address.to_string(), Network::Testnet).unwrap();
+        assert_eq!(utxos[0].script_hex, hex::encode(address.script_pubkey().as_bytes()));
+        assert_eq!(utxos[0].value_satoshis, 150_000_000);
+        assert_eq!(utxos[1].script_hex, "51");
+
+        // Without a parseable address the script stays unknown
+        let utxos = ChainSoClient::with_base_url(&url).get_tx_unspent("unparseable", Network::Testnet).unwrap();
+        assert!(utxos[0].script_hex.is_empty());
+    }
+
+    #[test]
+    fn test_utxo_conversion_roundtrip() {
+        let original = ExplorerUtxo {
+            txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+            vout: 3,
+            value_satoshis: 123_456_789,
+            script_hex: "76a914111111111111111111111111111111111111111188ac".to_string(),
+            confirmations: 42,
+            is_coinbase: true,
+        };
+
+        let info: UtxoInfo = original.clone().into();
+        assert_eq!(info.txid, original.txid);
+        assert_eq!(info.vout, original.vout);
+        assert_eq!(info.value, original.value_satoshis);
+        assert_eq!(info.script_pubkey, original.script_hex);
+        assert_eq!(info.confirmations, original.confirmations);
+        assert_eq!(info.is_coinbase, original.is_coinbase);
+
+        let back: ExplorerUtxo = info.into();
+        assert_eq!(back.txid, original.txid);
+        assert_eq!(back.vout, original.vout);
+        assert_eq!(back.value_satoshis, original.value_satoshis);
+        assert_eq!(back.script_hex, original.script_hex);
+        assert_eq!(back.confirmations, original.confirmations);
+        assert_eq!(back.is_coinbase, original.is_coinbase);
+    }
+
+    #[test]
+    fn test_get_tx_unspent_pagination() {
+        let page = |range: std::ops::Range<usize>| {
+            let txs: Vec<_> = range
+                .map(|i| json!({ "txid": format!("{i:064x}"), "output_no": 0, "value": "1.0", "script_hex": "51", "confirmations": 3 }))
+                .collect();
+            json!({ "status": "success", "data": { "txs": txs } }).to_string()
+        };
+        let (url, requests) = mock_server(vec![page(0..CHAIN_SO_PAGE_SIZE), page(CHAIN_SO_PAGE_SIZE - 1..CHAIN_SO_PAGE_SIZE + 30)]);
+
+        let utxos = ChainSoClient::with_base_url(&url).get_tx_unspent("addr", Network::Testnet).unwrap();
+        assert_eq!(utxos.len(), CHAIN_SO_PAGE_SIZE + 30);
+        assert_eq!(utxos.last().unwrap().txid, format!("{:064x}", CHAIN_SO_PAGE_SIZE + 29));
+
+        assert!(requests.recv().unwrap().head.starts_with("GET /get_tx_unspent/DOGETEST/addr "));
+        let cursor = format!("GET /get_tx_unspent/DOGETEST/addr/{:064x} ", CHAIN_SO_PAGE_SIZE - 1);
+        assert!(requests.recv().unwrap().head.starts_with(&cursor));
+    }
+
+    #[test]
+    fn test_balances_continue_past_failures() {
+        let balance = |b: &str| json!({ "status": "success", "data": { "confirmed_balance": b } }).to_string();
+        let failure = json!({ "status": "fail", "data": { "address": "bad" } }).to_string();
+        let (url, requests) = mock_server(vec![balance("12.5"), failure, balance("0.00000001")]);
+
+        let results = ChainSoClient::with_base_url(&url).balances(&["a1", "bad", "a3"], Network::Testnet);
+        let names: Vec<&str> = results.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(names, ["a1", "bad", "a3"]);
+        assert_eq!(results[0].1.as_ref().unwrap(), &1_250_000_000);
+        assert_eq!(results[1].1.as_ref().unwrap_err().to_string(), "chain.so status: fail");
+        assert_eq!(results[2].1.as_ref().unwrap(), &1);
+
+        assert!(requests.recv().unwrap().head.starts_with("GET /get_address_balance/DOGETEST/a1 "));
+    }
+
+    struct FailingProvider;
+
+    impl UtxoProvider for FailingProvider {
+        fn get_utxos(&self, _address: &str, _network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+            Err("provider down".into())
+        }
+
+        fn has_history(&self, _address: &str, _network: Network) -> Result<bool, Box<dyn Error>> {
+            Err("provider down".into())
+        }
+    }
+
+    #[test]
+    fn test_has_history() {
+        let received = |txs: serde_json::Value| json!({ "status": "success", "data": { "txs": txs } }).to_string();
+        let spent = json!([{ "txid": "aa", "output_no": 0, "value": "1.0", "confirmations": 9 }]);
+        let (url, requests) = mock_server(vec![received(spent), received(json!([]))]);
+
+        let v2 = ChainSoClient::with_base_url(&url);
+        assert!(v2.has_history("addr", Network::Testnet).unwrap());
+        assert!(!v2.has_history("fresh", Network::Testnet).unwrap());
+        assert!(requests.recv().unwrap().head.starts_with("GET /get_tx_received/DOGETEST/addr "));
+
+        let transactions = |t: serde_json::Value| json!({ "status": "success", "data": { "transactions": t } }).to_string();
+        let (url, requests) = mock_server(vec![transactions(json!([{ "hash": "aa" }])), transactions(json!([]))]);
+        let v3 = SoChainV3Client::new("key").with_base_url(&url);
+        assert!(v3.has_history("addr", Network::Mainnet).unwrap());
+        assert!(!v3.has_history("fresh", Network::Mainnet).unwrap());
+        assert!(requests.recv().unwrap().head.starts_with("GET /transactions/DOGE/addr/1 "));
+
+        let rpc_ok = |result: serde_json::Value| json!({ "result": result, "error": null, "id": 1 }).to_string();
+        let mine = rpc_ok(json!({ "ismine": false, "iswatchonly": true }));
+        let (url, requests) = mock_server(vec![mine.clone(), rpc_ok(json!(2.5)), mine, rpc_ok(json!(0.0))]);
+        let node = DogeRpcClient::new(&url, None, None);
+        assert!(node.has_history("addr", Network::Mainnet).unwrap());
+        assert!(!node.has_history("fresh", Network::Mainnet).unwrap());
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getaddressinfo");
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getreceivedbyaddress");
+        assert_eq!(request["params"], json!(["addr", 0]));
+    }
+
+    #[test]
+    fn test_node_history_of_non_wallet_address() {
+        let rpc_ok = |result: serde_json::Value| json!({ "result": result, "error": null, "id": 1 }).to_string();
+        let not_mine = rpc_ok(json!({ "ismine": false, "iswatchonly": false }));
+        let scan = |unspents: serde_json::Value| rpc_ok(json!({ "success": true, "height": 100, "unspents": unspents }));
+        let unspent = json!([{ "txid": "aa".repeat(32), "vout": 0, "amount": 1.0, "scriptPubKey": "51", "height": 90 }]);
+        let (url, requests) = mock_server(vec![not_mine.clone(), scan(unspent), not_mine, scan(json!([]))]);
+        let node = DogeRpcClient::new(&url, None, None);
+
+        assert!(node.has_history("funded", Network::Mainnet).unwrap());
+        let err = node.has_history("unknown", Network::Mainnet).unwrap_err();
+        assert!(err.to_string().contains("isn't in the node wallet"), "{err}");
+        let methods: Vec<String> = requests
+            .try_iter()
+            .map(|r| serde_json::from_str::<serde_json::Value>(&r.body).unwrap()["method"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(methods, ["getaddressinfo", "scantxoutset", "getaddressinfo", "scantxoutset"]);
+    }
+
+    #[test]
+    fn test_is_wallet_address_falls_back_to_validateaddress() {
+        let not_found = json!({ "result": null, "error": { "code": -32601, "message": "Method not found" }, "id": 1 });
+        let valid = json!({ "result": { "isvalid": true, "ismine": true }, "error": null, "id": 1 });
+        let (url, requests) = mock_server(vec![not_found.to_string(), valid.to_string()]);
+        let node = DogeRpcClient::new(&url, None, None);
+
+        assert!(node.is_wallet_address("addr").unwrap());
+        requests.recv().unwrap();
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "validateaddress");
+    }
+
+    #[test]
+    fn test_utxo_pool_fallback() {
+        let body = json!({
+            "status": "success",
+            "data": { "txs": [{ "txid": "aa", "output_no": 0, "value": "1.0", "script_hex": "51", "confirmations": 3 }] }
+        })
+        .to_string();
+        let (url, _requests) = mock_server(vec![body]);
+
+        let pool = UtxoPool::new()
+            .with_provider(FailingProvider)
+            .with_provider(ChainSoClient::with_base_url(&url));
+        let utxos = pool.get_utxos("addr", Network::Testnet).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].value_satoshis, 100_000_000);
+
+        let pool = UtxoPool::new().with_provider(FailingProvider).with_provider(FailingProvider);
+        let err = pool.get_utxos("addr", Network::Testnet).unwrap_err();
+        assert_eq!(err.0, vec!["provider down", "provider down"]);
+    }
+
+    fn v2_failure() -> (String, std::sync::mpsc::Receiver<crate::test_util::MockRequest>) {
+        mock_server(vec![json!({ "status": "fail", "data": { "txs": [] } }).to_string()])
+    }
+
+    #[test]
+    fn test_utxo_pool_falls_back_to_v3() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let outputs = |o: Vec<serde_json::Value>| json!({ "status": "success", "data": { "outputs": o } }).to_string();
+        let (v2_url, _v2) = v2_failure();
+        let (v3_url, v3_requests) = mock_server(vec![
+            outputs(vec![
+                json!({ "hash": txid, "index": 1, "script": "51", "address": "addr", "value": "2.5", "block": 5_000_000 }),
+                json!({ "hash": txid, "index": 2, "script": "51", "address": "addr", "value": "0.1", "block": null }),
+            ]),
+            outputs(vec![]),
+        ]);
+
+        let pool = UtxoPool::new()
+            .with_provider(ChainSoClient::with_base_url(&v2_url))
+            .with_provider(SoChainV3Client::new("key").with_base_url(&v3_url));
+        let utxos = pool.get_utxos("addr", Network::Testnet).unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!((utxos[0].vout, utxos[0].value_satoshis, utxos[0].confirmations), (1, 250_000_000, 1));
+        assert_eq!((utxos[1].value_satoshis, utxos[1].confirmations), (10_000_000, 0));
+
+        let first = v3_requests.recv().unwrap();
+        assert!(first.head.starts_with("GET /unspent_outputs/DOGETEST/addr/1 "));
+        assert!(first.head.to_ascii_lowercase().contains("api-key: key"));
+        assert!(v3_requests.recv().unwrap().head.starts_with("GET /unspent_outputs/DOGETEST/addr/2 "));
+    }
+
+    #[test]
+    fn test_utxo_pool_falls_back_to_node() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let rpc_ok = |result: serde_json::Value| json!({ "result": result, "error": null, "id": 1 }).to_string();
+        let unspent = json!({ "txid": txid, "vout": 0, "scriptPubKey": "51", "amount": 1.0, "height": 100 });
+        let (v2_url, _v2) = v2_failure();
+        let (rpc_url, rpc_requests) = mock_server(vec![
+            rpc_ok(json!({ "success": true, "height": 104, "unspents": [unspent] })),
+            json!({ "result": null, "error": { "code": -32601, "message": "Method not found" }, "id": 1 }).to_string(),
+            rpc_ok(json!([{ "txid": txid, "vout": 3, "scriptPubKey": "51", "amount": 0.5, "confirmations": 7 }])),
+        ]);
+        let node = DogeRpcClient::new(&rpc_url, None, None);
+
+        let pool = UtxoPool::new().with_provider(ChainSoClient::with_base_url(&v2_url)).with_provider(node);
+        let utxos = pool.get_utxos("addr", Network::Testnet).unwrap();
+        assert_eq!((utxos[0].value_satoshis, utxos[0].confirmations), (100_000_000, 5));
+        let request: serde_json::Value = serde_json::from_str(&rpc_requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["params"], json!(["start", ["addr(addr)"]]));
+
+        // A 1.14 node without scantxoutset answers through listunspent
+        let node = DogeRpcClient::new(&rpc_url, None, None);
+        let utxos = node.get_utxos("addr", Network::Testnet).unwrap();
+        assert_eq!((utxos[0].vout, utxos[0].value_satoshis, utxos[0].confirmations), (3, 50_000_000, 7));
+        rpc_requests.recv().unwrap();
+        let request: serde_json::Value = serde_json::from_str(&rpc_requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "listunspent");
+        assert_eq!(request["params"], json!([0, 9_999_999, ["addr"]]));
+    }
+
+    #[test]
+    fn test_node_scan_failure_is_not_an_empty_wallet() {
+        let (url, requests) = mock_server(vec![
+            json!({ "result": null, "error": { "code": -8, "message": "Scan already in progress" }, "id": 1 }).to_string(),
+        ]);
+        let node = DogeRpcClient::new(&url, None, None);
+
+        let err = node.get_utxos("addr", Network::Testnet).unwrap_err();
+        assert_eq!(err.downcast_ref::<RpcError>().map(|e| e.code), Some(-8));
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "scantxoutset");
+        // No listunspent fallback was attempted
+        assert!(requests.try_recv().is_err());
+    }
+}
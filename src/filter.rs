@@ -0,0 +1,134 @@
+//! BIP158 compact block filters.
+//!
+//! A filter is a Golomb-coded set of the scripts a block touches, keyed by the block hash.
+//! A light client downloads filters, tests its own scripts locally and only fetches the
+//! blocks that match, without telling an explorer which addresses it cares about.
+//!
+//! The GCS encoding (P = 19, M = 784931) comes from `bitcoin::bip158`.
+
+use bitcoin::bip158::{self, BlockFilter, BlockFilterWriter};
+use bitcoin::{Block, BlockHash, OutPoint, ScriptBuf};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterError {
+    /// The script of an outpoint spent in the block couldn't be looked up
+    MissingPrevout(OutPoint),
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::MissingPrevout(o) => write!(f, "missing prevout script for {o}"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// Build a filter over the block's output scripts (OP_RETURN outputs excluded).
+///
+/// A block alone doesn't carry the scripts its inputs spend, so this only covers
+/// payments received. Use `build_block_filter_with_prevouts` for a full BIP158 basic filter.
+pub fn build_block_filter(block: &Block) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut writer = BlockFilterWriter::new(&mut out, block);
+    writer.add_output_scripts();
+    writer.finish().expect("writing to a Vec can't fail");
+    out
+}
+
+/// Build a BIP158 basic filter: output scripts plus the scripts of every outpoint spent,
+/// looked up with `prevout_script`.
+pub fn build_block_filter_with_prevouts<F>(block: &Block, prevout_script: F) -> Result<Vec<u8>, FilterError>
+where
+    F: Fn(&OutPoint) -> Option<ScriptBuf>,
+{
+    let mut out = Vec::new();
+    let mut writer = BlockFilterWriter::new(&mut out, block);
+    writer.add_output_scripts();
+    writer
+        .add_input_scripts(|outpoint| prevout_script(outpoint).ok_or(bip158::Error::UtxoMissing(*outpoint)))
+        .map_err(|e| match e {
+            bip158::Error::UtxoMissing(outpoint) => FilterError::MissingPrevout(outpoint),
+            e => unreachable!("unexpected filter error: {e}"),
+        })?;
+    writer.finish().expect("writing to a Vec can't fail");
+    Ok(out)
+}
+
+/// Whether any of `scripts` may be in the filter of block `block_hash`.
+///
+/// False positives happen about once in 784931 queries; false negatives never do.
+/// A malformed filter counts as a match, so the caller fetches the block rather than
+/// silently missing a payment.
+pub fn filter_matches(filter: &[u8], block_hash: &BlockHash, scripts: &[ScriptBuf]) -> bool {
+    BlockFilter::new(filter)
+        .match_any(block_hash, scripts.iter().map(|s| s.as_bytes()))
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::DogeAddress;
+    use crate::network::Network;
+    use crate::transaction::TransactionBuilder;
+    use bitcoin::block::{Header, Version};
+    use bitcoin::hashes::Hash;
+    use bitcoin::{CompactTarget, TxMerkleNode};
+
+    fn block_paying(to: &DogeAddress) -> Block {
+        let mut coinbase = TransactionBuilder::new();
+        coinbase.add_input("0000000000000000000000000000000000000000000000000000000000000000", u32::MAX);
+        coinbase.add_output(&DogeAddress::from_pubkey_hash(&[0x01; 20], Network::Mainnet), 10_000 * 100_000_000).unwrap();
+
+        let mut payment = TransactionBuilder::new();
+        payment.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        payment.add_output(to, 100_000_000).unwrap();
+
+        Block {
+            header: Header {
+                version: Version::ONE,
+                prev_blockhash: BlockHash::all_zeros(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 1_700_000_000,
+                bits: CompactTarget::from_consensus(0x1e0ffff0),
+                nonce: 0,
+            },
+            txdata: vec![coinbase.build(), payment.build()],
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_outputs() {
+        let ours = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        let other = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Mainnet);
+        let block = block_paying(&ours);
+        let filter = build_block_filter(&block);
+        let hash = block.block_hash();
+
+        assert!(filter_matches(&filter, &hash, &[other.script_pubkey(), ours.script_pubkey()]));
+        assert!(!filter_matches(&filter, &hash, &[other.script_pubkey()]));
+        assert!(!filter_matches(&filter, &hash, &[]));
+        // Filters are keyed by block hash
+        assert!(!filter_matches(&filter, &BlockHash::all_zeros(), &[ours.script_pubkey()]));
+    }
+
+    #[test]
+    fn test_filter_with_prevouts() {
+        let ours = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        let spent = DogeAddress::from_pubkey_hash(&[0x33; 20], Network::Mainnet);
+        let block = block_paying(&ours);
+        let hash = block.block_hash();
+
+        assert!(!filter_matches(&build_block_filter(&block), &hash, &[spent.script_pubkey()]));
+
+        let filter = build_block_filter_with_prevouts(&block, |_| Some(spent.script_pubkey())).unwrap();
+        assert!(filter_matches(&filter, &hash, &[spent.script_pubkey()]));
+        assert!(filter_matches(&filter, &hash, &[ours.script_pubkey()]));
+
+        let missing = block.txdata[1].input[0].previous_output;
+        assert_eq!(build_block_filter_with_prevouts(&block, |_| None), Err(FilterError::MissingPrevout(missing)));
+    }
+}
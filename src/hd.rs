@@ -0,0 +1,321 @@
+//! BIP32 hierarchical deterministic keys.
+//!
+//! Dogecoin reuses BIP32 unchanged; only the extended-key version bytes differ
+//! (`dgub`/`dgpv` on mainnet). Keys are decoded with either Bitcoin or Dogecoin
+//! version bytes and addresses are derived with Dogecoin's address prefixes.
+
+use bitcoin::base58;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpub};
+use bitcoin::secp256k1::Secp256k1;
+use std::fmt;
+
+use crate::address::DogeAddress;
+use crate::explorer::UtxoProvider;
+use crate::network::Network;
+
+/// Dogecoin mainnet extended public key version (`dgub`)
+pub const DOGE_XPUB_VERSION: [u8; 4] = [0x02, 0xfa, 0xca, 0xfd];
+const BITCOIN_XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const BITCOIN_TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+/// External (receive) chain index
+pub const RECEIVE_CHAIN: u32 = 0;
+/// Internal (change) chain index
+pub const CHANGE_CHAIN: u32 = 1;
+
+#[derive(Debug)]
+pub enum HdError {
+    InvalidBase58Check(String),
+    UnknownVersion([u8; 4]),
+    InvalidKey(String),
+    Derivation(String),
+    /// The UTXO provider failed while scanning
+    Explorer(String),
+}
+
+impl fmt::Display for HdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdError::InvalidBase58Check(e) => write!(f, "invalid base58check: {e}"),
+            HdError::UnknownVersion(v) => write!(f, "unknown extended key version: {}", hex::encode(v)),
+            HdError::InvalidKey(e) => write!(f, "invalid extended key: {e}"),
+            HdError::Derivation(e) => write!(f, "derivation failed: {e}"),
+            HdError::Explorer(e) => write!(f, "explorer query failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HdError {}
+
+/// Parse an extended public key in `dgub`, `xpub` or `tpub` form
+pub fn parse_xpub(s: &str) -> Result<Xpub, HdError> {
+    let mut data = base58::decode_check(s).map_err(|e| HdError::InvalidBase58Check(e.to_string()))?;
+    if data.len() != 78 {
+        return Err(HdError::InvalidKey(format!("invalid length: {}", data.len())));
+    }
+
+    let version: [u8; 4] = data[..4].try_into().expect("4 bytes");
+    if version == DOGE_XPUB_VERSION {
+        data[..4].copy_from_slice(&BITCOIN_XPUB_VERSION);
+    } else if version != BITCOIN_XPUB_VERSION && version != BITCOIN_TPUB_VERSION {
+        return Err(HdError::UnknownVersion(version));
+    }
+
+    Xpub::decode(&data).map_err(|e| HdError::InvalidKey(e.to_string()))
+}
+
+/// Default BIP44 account path `m/44'/coin_type'/account'` for `network`
+pub fn account_path(network: Network, account: u32) -> Result<DerivationPath, HdError> {
+    let hardened = |i| ChildNumber::from_hardened_idx(i).map_err(|e| HdError::Derivation(e.to_string()));
+    Ok(DerivationPath::from(vec![hardened(44)?, hardened(network.bip44_coin_type())?, hardened(account)?]))
+}
+
+/// Derive the P2PKH address at `chain/index` below an account-level xpub
+///
+/// The xpub is expected at the default `account_path`, giving the full path
+/// `m/44'/coin_type'/account'/chain/index`.
+pub fn derive_address(xpub: &Xpub, chain: u32, index: u32, network: Network) -> Result<DogeAddress, HdError> {
+    let secp = Secp256k1::verification_only();
+    let path = [
+        ChildNumber::from_normal_idx(chain).map_err(|e| HdError::Derivation(e.to_string()))?,
+        ChildNumber::from_normal_idx(index).map_err(|e| HdError::Derivation(e.to_string()))?,
+    ];
+    let child = xpub.derive_pub(&secp, &path).map_err(|e| HdError::Derivation(e.to_string()))?;
+    Ok(DogeAddress::from_pubkey(&child.public_key, network))
+}
+
+/// Watch-only address derivation: the receive (or, with `change`, change) address at
+/// `index` below an account-level xpub. No private key is involved.
+///
+/// Fails only for hardened indices (`>= 2^31`), which an xpub can't derive.
+pub fn address_from_xpub(xpub: &Xpub, change: bool, index: u32, network: Network) -> Result<DogeAddress, HdError> {
+    let chain = if change { CHANGE_CHAIN } else { RECEIVE_CHAIN };
+    derive_address(xpub, chain, index, network)
+}
+
+/// An address found in use during `discover_used_addresses`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsedAddress {
+    pub chain: u32,
+    pub index: u32,
+    pub address: DogeAddress,
+    /// Sum of the address's unspent outputs in satoshis
+    pub balance: u64,
+}
+
+/// Result of account discovery below an xpub
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletState {
+    /// Used addresses, receive chain first, each chain in index order
+    pub used: Vec<UsedAddress>,
+    /// First receive index after the last used one
+    pub next_receive_index: u32,
+    pub next_receive_address: DogeAddress,
+}
+
+impl WalletState {
+    /// Total balance across all used addresses
+    pub fn balance(&self) -> u64 {
+        self.used.iter().map(|u| u.balance).sum()
+    }
+}
+
+/// Account discovery as run on wallet restore.
+///
+/// Walks the receive and change chains below an account-level xpub, querying `explorer`
+/// for each address, and stops each chain after `gap` consecutive unused addresses
+/// (BIP44 uses 20). An address counts as used if it has any transaction history, so a
+/// change address that was later emptied still extends the scan and is never handed out
+/// again as `next_receive_address`.
+pub fn discover_used_addresses(
+    xpub: &Xpub,
+    explorer: &dyn UtxoProvider,
+    network: Network,
+    gap: u32,
+) -> Result<WalletState, HdError> {
+    let explorer_err = |e: Box<dyn std::error::Error>| HdError::Explorer(e.to_string());
+    let mut used = Vec::new();
+    let mut next_receive_index = 0;
+    for chain in [RECEIVE_CHAIN, CHANGE_CHAIN] {
+        let mut unused_run = 0;
+        let mut index = 0;
+        while unused_run < gap {
+            let address = derive_address(xpub, chain, index, network)?;
+            if explorer.has_history(&address.to_string(), network).map_err(explorer_err)? {
+                unused_run = 0;
+                if chain == RECEIVE_CHAIN {
+                    next_receive_index = index + 1;
+                }
+                let utxos = explorer.get_utxos(&address.to_string(), network).map_err(explorer_err)?;
+                let balance = utxos.iter().map(|u| u.value_satoshis).sum();
+                used.push(UsedAddress { chain, index, address, balance });
+            } else {
+                unused_run += 1;
+            }
+            index += 1;
+        }
+    }
+
+    Ok(WalletState {
+        used,
+        next_receive_index,
+        next_receive_address: derive_address(xpub, RECEIVE_CHAIN, next_receive_index, network)?,
+    })
+}
+
+/// Check whether `address` was derived from `xpub`.
+///
+/// Scans the receive and change chains for indices `0..=max_index` and returns the
+/// matching `(chain, index)`, or `None` if the address isn't found (or the xpub is invalid).
+pub fn address_in_xpub(xpub: &str, address: &DogeAddress, max_index: u32, network: Network) -> Option<(u32, u32)> {
+    let xpub = parse_xpub(xpub).ok()?;
+    for index in 0..=max_index {
+        for chain in [RECEIVE_CHAIN, CHANGE_CHAIN] {
+            if derive_address(&xpub, chain, index, network).ok().as_ref() == Some(address) {
+                return Some((chain, index));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorer::ExplorerUtxo;
+    use bitcoin::bip32::Xpriv;
+
+    fn test_xpub() -> Xpub {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(bitcoin::Network::Bitcoin, &[0x42; 32]).unwrap();
+        Xpub::from_priv(&secp, &master)
+    }
+
+    #[test]
+    fn test_parse_xpub_versions() {
+        let xpub = test_xpub();
+        let xpub_str = xpub.to_string();
+        assert!(xpub_str.starts_with("xpub"));
+        assert_eq!(parse_xpub(&xpub_str).unwrap(), xpub);
+
+        let mut data = xpub.encode().to_vec();
+        data[..4].copy_from_slice(&DOGE_XPUB_VERSION);
+        let dgub = base58::encode_check(&data);
+        assert!(dgub.starts_with("dgub"));
+        assert_eq!(parse_xpub(&dgub).unwrap(), xpub);
+
+        data[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        assert!(matches!(parse_xpub(&base58::encode_check(&data)), Err(HdError::UnknownVersion(_))));
+    }
+
+    #[test]
+    fn test_address_in_xpub() {
+        let xpub = test_xpub();
+        let xpub_str = xpub.to_string();
+
+        let receive_5 = derive_address(&xpub, RECEIVE_CHAIN, 5, Network::Mainnet).unwrap();
+        assert_eq!(address_in_xpub(&xpub_str, &receive_5, 10, Network::Mainnet), Some((0, 5)));
+        assert_eq!(address_in_xpub(&xpub_str, &receive_5, 4, Network::Mainnet), None);
+
+        let change_2 = derive_address(&xpub, CHANGE_CHAIN, 2, Network::Mainnet).unwrap();
+        assert_eq!(address_in_xpub(&xpub_str, &change_2, 10, Network::Mainnet), Some((1, 2)));
+
+        let unrelated = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        assert_eq!(address_in_xpub(&xpub_str, &unrelated, 10, Network::Mainnet), None);
+    }
+
+    #[test]
+    fn test_address_from_xpub_matches_private_derivation() {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(bitcoin::Network::Bitcoin, &[0x42; 32]).unwrap();
+        let account = master.derive_priv(&secp, &account_path(Network::Mainnet, 0).unwrap()).unwrap();
+        let xpub = Xpub::from_priv(&secp, &account);
+
+        for (change, chain) in [(false, RECEIVE_CHAIN), (true, CHANGE_CHAIN)] {
+            let path = [ChildNumber::from_normal_idx(chain).unwrap(), ChildNumber::from_normal_idx(7).unwrap()];
+            let child = account.derive_priv(&secp, &path).unwrap();
+            let expected = DogeAddress::from_pubkey(&child.private_key.public_key(&secp), Network::Mainnet);
+            assert_eq!(address_from_xpub(&xpub, change, 7, Network::Mainnet).unwrap(), expected);
+        }
+        assert_ne!(
+            address_from_xpub(&xpub, false, 7, Network::Mainnet).unwrap(),
+            address_from_xpub(&xpub, true, 7, Network::Mainnet).unwrap()
+        );
+        assert!(address_from_xpub(&xpub, false, 1 << 31, Network::Mainnet).is_err());
+    }
+
+    /// Serves fixed UTXOs per address; a 0 value marks an address whose coins were all
+    /// spent. Everything else has no history.
+    struct FixedProvider(Vec<(DogeAddress, u64)>);
+
+    impl UtxoProvider for FixedProvider {
+        fn get_utxos(&self, address: &str, _network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn std::error::Error>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|(a, value)| a.to_string() == address && *value > 0)
+                .map(|(_, value)| ExplorerUtxo {
+                    txid: "aa".to_string(),
+                    vout: 0,
+                    value_satoshis: *value,
+                    script_hex: String::new(),
+                    confirmations: 10,
+                    is_coinbase: false,
+                })
+                .collect())
+        }
+
+        fn has_history(&self, address: &str, _network: Network) -> Result<bool, Box<dyn std::error::Error>> {
+            Ok(self.0.iter().any(|(a, _)| a.to_string() == address))
+        }
+    }
+
+    #[test]
+    fn test_discover_used_addresses() {
+        let xpub = test_xpub();
+        let addr = |chain, index| derive_address(&xpub, chain, index, Network::Mainnet).unwrap();
+        let provider = FixedProvider(vec![
+            (addr(RECEIVE_CHAIN, 0), 100),
+            (addr(RECEIVE_CHAIN, 3), 200),
+            // Beyond a gap of 3 after index 3, so never reached
+            (addr(RECEIVE_CHAIN, 7), 400),
+            (addr(CHANGE_CHAIN, 1), 50),
+        ]);
+
+        let state = discover_used_addresses(&xpub, &provider, Network::Mainnet, 3).unwrap();
+        let found: Vec<(u32, u32, u64)> = state.used.iter().map(|u| (u.chain, u.index, u.balance)).collect();
+        assert_eq!(found, vec![(0, 0, 100), (0, 3, 200), (1, 1, 50)]);
+        assert_eq!(state.balance(), 350);
+        assert_eq!(state.next_receive_index, 4);
+        assert_eq!(state.next_receive_address, addr(RECEIVE_CHAIN, 4));
+
+        let state = discover_used_addresses(&xpub, &provider, Network::Mainnet, 4).unwrap();
+        assert_eq!(state.next_receive_index, 8);
+        assert_eq!(state.balance(), 750);
+
+        // Emptied addresses still count as used: they keep the scan going past them
+        let provider = FixedProvider(vec![
+            (addr(RECEIVE_CHAIN, 0), 0),
+            (addr(RECEIVE_CHAIN, 1), 0),
+            (addr(RECEIVE_CHAIN, 3), 200),
+            (addr(CHANGE_CHAIN, 0), 0),
+            (addr(CHANGE_CHAIN, 2), 75),
+        ]);
+        let state = discover_used_addresses(&xpub, &provider, Network::Mainnet, 2).unwrap();
+        let found: Vec<(u32, u32, u64)> = state.used.iter().map(|u| (u.chain, u.index, u.balance)).collect();
+        assert_eq!(found, vec![(0, 0, 0), (0, 1, 0), (0, 3, 200), (1, 0, 0), (1, 2, 75)]);
+        assert_eq!(state.balance(), 275);
+        assert_eq!(state.next_receive_address, addr(RECEIVE_CHAIN, 4));
+
+        let empty = discover_used_addresses(&xpub, &FixedProvider(Vec::new()), Network::Mainnet, 20).unwrap();
+        assert!(empty.used.is_empty());
+        assert_eq!(empty.next_receive_address, addr(RECEIVE_CHAIN, 0));
+    }
+
+    #[test]
+    fn test_account_path_uses_coin_type() {
+        assert_eq!(account_path(Network::Mainnet, 0).unwrap().to_string(), "44'/3'/0'");
+        assert_eq!(account_path(Network::Testnet, 2).unwrap().to_string(), "44'/1'/2'");
+        assert!(account_path(Network::Mainnet, 1 << 31).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Local script verification for standard transactions.
+//!
+//! A small interpreter that runs each input's scriptSig and its prevout scriptPubKey
+//! (and, for P2SH, the redeem script) the way a node would, so signing or script-assembly
+//! mistakes are caught before broadcast. It covers the opcodes used by standard templates
+//! (P2PKH, P2PK, bare and P2SH multisig); any other opcode fails verification.
+//!
+//! Flags enforced, matching Dogecoin Core 1.14's standard policy:
+//!
+//! - `P2SH` (BIP16): the redeem script is evaluated for P2SH outputs
+//! - `DERSIG` (BIP66), `LOW_S` and `STRICTENC`: signatures must be canonical DER with a low S
+//!   and a defined sighash type; public keys must be valid encodings
+//! - `SIGPUSHONLY`: scriptSigs may only push data
+//! - `NULLDUMMY`: the extra CHECKMULTISIG stack item must be empty
+//! - `NULLFAIL`: a non-empty signature that fails verification is an error, not `false`
+//! - `CLEANSTACK`: exactly one item may remain after evaluation
+//!
+//! Timelock opcodes (CLTV/CSV) are not evaluated and fail as unsupported.
+
+use bitcoin::hashes::{hash160, ripemd160, sha256, sha256d, Hash};
+use bitcoin::opcodes::all::*;
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{ecdsa, Message, PublicKey, Secp256k1, VerifyOnly};
+use bitcoin::sighash::SighashCache;
+use bitcoin::{Script, ScriptBuf, Transaction, TxOut};
+use std::fmt;
+
+use crate::transaction::is_canonical_der;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScriptValidationError {
+    PrevoutCountMismatch { inputs: usize, prevouts: usize },
+    /// Input `input` failed verification
+    Failed { input: usize, reason: String },
+}
+
+impl fmt::Display for ScriptValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptValidationError::PrevoutCountMismatch { inputs, prevouts } => {
+                write!(f, "{inputs} inputs but {prevouts} prevouts")
+            }
+            ScriptValidationError::Failed { input, reason } => write!(f, "input {input} failed script verification: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ScriptValidationError {}
+
+/// Run every input's scripts against its prevout (`prevouts[i]` is spent by input `i`)
+pub fn validate_scripts(tx: &Transaction, prevouts: &[TxOut]) -> Result<(), ScriptValidationError> {
+    if tx.input.len() != prevouts.len() {
+        return Err(ScriptValidationError::PrevoutCountMismatch { inputs: tx.input.len(), prevouts: prevouts.len() });
+    }
+
+    let interpreter = Interpreter { tx, cache: SighashCache::new(tx), secp: Secp256k1::verification_only() };
+    for (input, prevout) in prevouts.iter().enumerate() {
+        interpreter
+            .verify_input(input, &prevout.script_pubkey)
+            .map_err(|reason| ScriptValidationError::Failed { input, reason })?;
+    }
+    Ok(())
+}
+
+struct Interpreter<'a> {
+    tx: &'a Transaction,
+    cache: SighashCache<&'a Transaction>,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl Interpreter<'_> {
+    fn verify_input(&self, input: usize, script_pubkey: &Script) -> Result<(), String> {
+        let script_sig = &self.tx.input[input].script_sig;
+        if !script_sig.is_push_only() {
+            return Err("scriptSig is not push-only".to_string());
+        }
+
+        let mut stack = Vec::new();
+        self.eval(input, script_sig, &mut stack)?;
+        let mut p2sh_stack = stack.clone();
+        self.eval(input, script_pubkey, &mut stack)?;
+        check_result(&stack)?;
+
+        if script_pubkey.is_p2sh() {
+            let redeem_script = ScriptBuf::from_bytes(p2sh_stack.pop().ok_or("empty scriptSig for P2SH")?);
+            self.eval(input, &redeem_script, &mut p2sh_stack)?;
+            check_result(&p2sh_stack)?;
+            stack = p2sh_stack;
+        }
+
+        if stack.len() != 1 {
+            return Err(format!("{} items left on the stack", stack.len()));
+        }
+        Ok(())
+    }
+
+    fn eval(&self, input: usize, script: &Script, stack: &mut Vec<Vec<u8>>) -> Result<(), String> {
+        for instruction in script.instructions() {
+            let op = match instruction.map_err(|e| e.to_string())? {
+                Instruction::PushBytes(data) => {
+                    stack.push(data.as_bytes().to_vec());
+                    continue;
+                }
+                Instruction::Op(op) => op,
+            };
+
+            let code = op.to_u8();
+            if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&code) {
+                stack.push(vec![code - OP_PUSHNUM_1.to_u8() + 1]);
+                continue;
+            }
+            match op {
+                OP_PUSHNUM_NEG1 => stack.push(vec![0x81]),
+                OP_NOP => {}
+                OP_DUP => stack.push(top(stack)?.clone()),
+                OP_DROP => {
+                    pop(stack)?;
+                }
+                OP_VERIFY => verify(pop(stack)?)?,
+                OP_HASH160 => {
+                    let data = pop(stack)?;
+                    stack.push(hash160::Hash::hash(&data).to_byte_array().to_vec());
+                }
+                OP_SHA256 => {
+                    let data = pop(stack)?;
+                    stack.push(sha256::Hash::hash(&data).to_byte_array().to_vec());
+                }
+                OP_HASH256 => {
+                    let data = pop(stack)?;
+                    stack.push(sha256d::Hash::hash(&data).to_byte_array().to_vec());
+                }
+                OP_RIPEMD160 => {
+                    let data = pop(stack)?;
+                    stack.push(ripemd160::Hash::hash(&data).to_byte_array().to_vec());
+                }
+                OP_EQUAL | OP_EQUALVERIFY => {
+                    let (b, a) = (pop(stack)?, pop(stack)?);
+                    let equal = bool_item(a == b);
+                    if op == OP_EQUALVERIFY {
+                        verify(equal).map_err(|_| "OP_EQUALVERIFY failed".to_string())?;
+                    } else {
+                        stack.push(equal);
+                    }
+                }
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                    let (pubkey, sig) = (pop(stack)?, pop(stack)?);
+                    let ok = bool_item(self.check_sig(input, script, &sig, &pubkey)?);
+                    if op == OP_CHECKSIGVERIFY {
+                        verify(ok).map_err(|_| "OP_CHECKSIGVERIFY failed".to_string())?;
+                    } else {
+                        stack.push(ok);
+                    }
+                }
+                OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                    let ok = bool_item(self.check_multisig(input, script, stack)?);
+                    if op == OP_CHECKMULTISIGVERIFY {
+                        verify(ok).map_err(|_| "OP_CHECKMULTISIGVERIFY failed".to_string())?;
+                    } else {
+                        stack.push(ok);
+                    }
+                }
+                op => return Err(format!("unsupported opcode {op}")),
+            }
+        }
+        Ok(())
+    }
+
+    /// `script_code` is the script being executed, as legacy sighash expects
+    fn check_sig(&self, input: usize, script_code: &Script, sig: &[u8], pubkey: &[u8]) -> Result<bool, String> {
+        let pubkey = PublicKey::from_slice(pubkey).map_err(|_| "invalid public key encoding".to_string())?;
+        if sig.is_empty() {
+            return Ok(false);
+        }
+        if !is_canonical_der(sig) {
+            return Err("non-canonical signature".to_string());
+        }
+
+        let (&sighash_type, der) = sig.split_last().expect("non-empty");
+        let signature = ecdsa::Signature::from_der(der).map_err(|e| e.to_string())?;
+        let sighash = self
+            .cache
+            .legacy_signature_hash(input, script_code, sighash_type as u32)
+            .map_err(|e| e.to_string())?;
+        let msg = Message::from_digest(sighash.to_byte_array());
+        match self.secp.verify_ecdsa(&msg, &signature, &pubkey) {
+            Ok(()) => Ok(true),
+            Err(_) => Err("signature does not verify".to_string()),
+        }
+    }
+
+    fn check_multisig(&self, input: usize, script_code: &Script, stack: &mut Vec<Vec<u8>>) -> Result<bool, String> {
+        let n = small_count(&pop(stack)?, 20)?;
+        let pubkeys: Vec<Vec<u8>> = (0..n).map(|_| pop(stack)).collect::<Result<_, _>>()?;
+        let m = small_count(&pop(stack)?, n)?;
+        let sigs: Vec<Vec<u8>> = (0..m).map(|_| pop(stack)).collect::<Result<_, _>>()?;
+        if !pop(stack)?.is_empty() {
+            return Err("CHECKMULTISIG dummy element is not empty".to_string());
+        }
+
+        // Items were popped in reverse; signatures must appear in pubkey order
+        let mut keys = pubkeys.iter().rev();
+        for sig in sigs.iter().rev() {
+            if sig.is_empty() {
+                return Ok(false);
+            }
+            loop {
+                let Some(pubkey) = keys.next() else {
+                    return Err("signature does not verify".to_string());
+                };
+                // A mismatched key isn't NULLFAIL; only a signature no key accepts is
+                if self.check_sig(input, script_code, sig, pubkey).unwrap_or(false) {
+                    break;
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> Result<Vec<u8>, String> {
+    stack.pop().ok_or_else(|| "stack underflow".to_string())
+}
+
+fn top(stack: &[Vec<u8>]) -> Result<&Vec<u8>, String> {
+    stack.last().ok_or_else(|| "stack underflow".to_string())
+}
+
+fn verify(item: Vec<u8>) -> Result<(), String> {
+    if cast_to_bool(&item) {
+        Ok(())
+    } else {
+        Err("OP_VERIFY failed".to_string())
+    }
+}
+
+fn check_result(stack: &[Vec<u8>]) -> Result<(), String> {
+    match stack.last() {
+        Some(item) if cast_to_bool(item) => Ok(()),
+        _ => Err("script evaluated to false".to_string()),
+    }
+}
+
+fn bool_item(b: bool) -> Vec<u8> {
+    if b {
+        vec![1]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Script truthiness: any non-zero byte, except a lone sign bit (negative zero)
+fn cast_to_bool(item: &[u8]) -> bool {
+    item.iter().enumerate().any(|(i, &b)| b != 0 && !(i == item.len() - 1 && b == 0x80))
+}
+
+/// Decode a key or signature count in `0..=max`
+fn small_count(item: &[u8], max: usize) -> Result<usize, String> {
+    let count = match item {
+        [] => 0,
+        [n] if *n < 0x80 => *n as usize,
+        _ => return Err("invalid CHECKMULTISIG count".to_string()),
+    };
+    if count > max {
+        return Err("invalid CHECKMULTISIG count".to_string());
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::DogeAddress;
+    use crate::network::Network;
+    use crate::script;
+    use crate::signer::Signer;
+    use crate::transaction::TransactionBuilder;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::Amount;
+
+    const TXID: &str = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+
+    fn prevout(script_pubkey: ScriptBuf) -> TxOut {
+        TxOut { value: Amount::from_sat(200_000_000), script_pubkey }
+    }
+
+    #[test]
+    fn test_validate_p2pkh() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&secret.public_key(&Secp256k1::new()), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(TXID, 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+        builder.sign_input(0, &secret, &from.script_pubkey()).unwrap();
+        let tx = builder.build();
+        let prevouts = [prevout(from.script_pubkey())];
+        validate_scripts(&tx, &prevouts).unwrap();
+
+        // Spending someone else's output: OP_EQUALVERIFY on the pubkey hash fails
+        let err = validate_scripts(&tx, &[prevout(to.script_pubkey())]).unwrap_err();
+        assert_eq!(err, ScriptValidationError::Failed { input: 0, reason: "OP_EQUALVERIFY failed".to_string() });
+
+        let mut tampered = tx.clone();
+        tampered.output[0].value = Amount::from_sat(150_000_000);
+        assert!(matches!(validate_scripts(&tampered, &prevouts), Err(ScriptValidationError::Failed { input: 0, .. })));
+
+        let mut unsigned = tx.clone();
+        unsigned.input[0].script_sig = ScriptBuf::new();
+        assert!(validate_scripts(&unsigned, &prevouts).is_err());
+
+        let mut not_push_only = tx.clone();
+        not_push_only.input[0].script_sig = bitcoin::script::Builder::new().push_opcode(OP_DUP).into_script();
+        let err = validate_scripts(&not_push_only, &prevouts).unwrap_err();
+        assert_eq!(err.to_string(), "input 0 failed script verification: scriptSig is not push-only");
+
+        assert_eq!(
+            validate_scripts(&tx, &[]),
+            Err(ScriptValidationError::PrevoutCountMismatch { inputs: 1, prevouts: 0 })
+        );
+    }
+
+    #[test]
+    fn test_validate_p2sh_multisig() {
+        let secp = Secp256k1::new();
+        let keys: Vec<SecretKey> = (1u8..=3).map(|i| SecretKey::from_slice(&[i; 32]).unwrap()).collect();
+        let pubkeys: Vec<Vec<u8>> = keys.iter().map(|k| k.public_key(&secp).serialize().to_vec()).collect();
+        let redeem = script::multisig_redeem_script(2, &pubkeys).unwrap();
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(TXID, 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+        let mut wrong_order = builder.clone();
+        let mut one_sig = builder.clone();
+        builder.sign_input_p2sh_multisig(0, &[&keys[0] as &dyn Signer, &keys[2]], &redeem).unwrap();
+        let prevouts = [prevout(script::p2sh_script_pubkey(&redeem))];
+        validate_scripts(&builder.build(), &prevouts).unwrap();
+
+        // CHECKMULTISIG needs signatures in pubkey order
+        wrong_order.sign_input_p2sh_multisig(0, &[&keys[2] as &dyn Signer, &keys[0]], &redeem).unwrap();
+        assert!(validate_scripts(&wrong_order.build(), &prevouts).is_err());
+
+        one_sig.sign_input_p2sh_multisig(0, &[&keys[1] as &dyn Signer], &redeem).unwrap();
+        assert!(validate_scripts(&one_sig.build(), &prevouts).is_err());
+    }
+
+    #[test]
+    fn test_cast_to_bool() {
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0, 0]));
+        assert!(!cast_to_bool(&[0, 0x80]));
+        assert!(cast_to_bool(&[1]));
+        assert!(cast_to_bool(&[0x80, 0]));
+    }
+}
@@ -0,0 +1,61 @@
+//! Local, off-chain transaction labels (memos) keyed by txid.
+//!
+//! Labels are bookkeeping only: they're stored in a JSON file next to the wallet
+//! and never touch the transaction itself.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File-backed `txid -> label` map. Every `set_label` rewrites the file.
+#[derive(Debug)]
+pub struct TxLabelStore {
+    path: PathBuf,
+    labels: BTreeMap<String, String>,
+}
+
+impl TxLabelStore {
+    /// Open the store at `path`, starting empty if the file doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let labels = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, labels })
+    }
+
+    pub fn set_label(&mut self, txid: &str, label: &str) -> io::Result<()> {
+        self.labels.insert(txid.to_string(), label.to_string());
+        let json = serde_json::to_string_pretty(&self.labels).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&self.path, json)
+    }
+
+    pub fn get_label(&self, txid: &str) -> Option<&str> {
+        self.labels.get(txid).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_round_trip() {
+        let path = std::env::temp_dir().join(format!("doge-hack-labels-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+
+        let mut store = TxLabelStore::open(&path).unwrap();
+        assert_eq!(store.get_label(txid), None);
+        store.set_label(txid, "rent for March").unwrap();
+        assert_eq!(store.get_label(txid), Some("rent for March"));
+
+        let reopened = TxLabelStore::open(&path).unwrap();
+        assert_eq!(reopened.get_label(txid), Some("rent for March"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -10,6 +10,20 @@
 //! - `transaction` - Transaction construction and signing
 //! - `network` - Network configuration (Testnet/Mainnet)
 //! - `rpc` - JSON-RPC client for node communication
+//! - `coinselect` - UTXO selection
+//! - `testnet` - Testnet presets (faucet sweep)
+//! - `merkle` - Merkle roots and inclusion proofs
+//! - `wif` - Wallet Import Format private keys
+//! - `uri` - `dogecoin:` payment request URIs
+//! - `partial` - Portable partially-signed transaction envelope
+//! - `hd` - BIP32 extended keys and address derivation
+//! - `amount` - DOGE amount parsing and formatting
+//! - `signer` - Signing abstraction for external key custody
+//! - `labels` - Off-chain transaction labels
+//! - `wallet` - High-level single-address wallet
+//! - `message` - Signed messages
+//! - `filter` - BIP158 compact block filters
+//! - `interpreter` - Local script verification
 
 pub mod address;
 pub mod transaction;
@@ -17,8 +31,28 @@ pub mod network;
 pub mod rpc;
 pub mod script;
 pub mod explorer;
+pub mod coinselect;
+pub mod testnet;
+pub mod merkle;
+pub mod wif;
+pub mod uri;
+pub mod partial;
+pub mod hd;
+pub mod amount;
+pub mod signer;
+pub mod labels;
+pub mod wallet;
+pub mod message;
+pub mod filter;
+pub mod interpreter;
+
+#[cfg(test)]
+mod test_util;
 
 pub use address::DogeAddress;
 pub use transaction::TransactionBuilder;
 pub use network::Network;
 pub use rpc::DogeRpcClient;
+
+/// Default User-Agent sent by the explorer and RPC clients
+pub const DEFAULT_USER_AGENT: &str = concat!("doge-hack/", env!("CARGO_PKG_VERSION"));
@@ -8,6 +8,7 @@ use doge_hack::network::Network;
 use doge_hack::transaction::TransactionBuilder;
 use doge_hack::rpc::DogeRpcClient;
 use doge_hack::script;
+use doge_hack::wif;
 use doge_hack::explorer::{ChainSoClient, SoChainV3Client};
 
 /// Doge-Hack: Dogecoin Transaction Constructor
@@ -247,7 +248,7 @@ fn cmd_gen_key(network: Network) {
 
     println!("Secret Key (hex): {}", hex::encode(secret_bytes));
     println!("Public Key: {}", public_key);
-    println!("Address: {}", address.to_string());
+    println!("Address: {}", address);
 }
 
 /// Derive address from a secret key
@@ -261,10 +262,11 @@ fn cmd_address(secret_key_hex: &str, network: Network) {
 
     println!("Network: {}", network);
     println!("Public Key: {}", public_key);
-    println!("Address: {}", address.to_string());
+    println!("Address: {}", address);
 }
 
 /// Construct and sign a transaction
+#[allow(clippy::too_many_arguments)]
 fn cmd_sign(
     txid: &str,
     vout: u32,
@@ -331,8 +333,8 @@ fn cmd_sign(
         }
     };
 
-    println!("From: {}", from_address.to_string());
-    println!("To: {}", to.to_string());
+    println!("From: {}", from_address);
+    println!("To: {}", to);
     println!("Input: {} sats", input_value);
     println!("Send: {} satoshis ({} DOGE)", amount, amount as f64 / 100_000_000.0);
     println!("Fee: {} satoshis ({} DOGE)", fee, fee as f64 / 100_000_000.0);
@@ -348,9 +350,12 @@ fn cmd_sign(
 
     let mut builder = TransactionBuilder::new();
     builder.add_input(txid, vout);
-    builder.add_output(&to, amount);
+    if let Err(e) = builder.add_output(&to, amount) {
+        eprintln!("ERROR: {e}");
+        return;
+    }
     if change_value > 0 {
-        builder.add_output(&change, change_value);
+        builder.add_output(&change, change_value).expect("change is non-zero");
     }
 
     // Sign
@@ -363,7 +368,7 @@ fn cmd_sign(
     };
     let prev_script_pubkey = bitcoin::ScriptBuf::from_bytes(prev_script_bytes);
 
-    builder.sign_input(0, &secret_key, &prev_script_pubkey);
+    builder.sign_input(0, &secret_key, &prev_script_pubkey).expect("fresh input");
 
     let signed_tx = builder.build();
     let signed_tx_hex = serialize_hex(&signed_tx);
@@ -372,10 +377,11 @@ fn cmd_sign(
     println!("{}", signed_tx_hex);
 
     if change_value > 0 {
-        println!("Change: {} sats -> {}", change_value, change.to_string());
+        println!("Change: {} sats -> {}", change_value, change);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn resolve_prevout(
     txid: &str,
     vout: u32,
@@ -441,7 +447,7 @@ fn cmd_p2sh_multisig(required: u8, pubkeys_hex: &[String], network: Network) {
 
     println!("Network: {}", network);
     println!("Redeem Script (hex): {}", hex::encode(redeem.as_bytes()));
-    println!("P2SH Address: {}", addr.to_string());
+    println!("P2SH Address: {}", addr);
 }
 
 fn cmd_fetch_utxos_address(address: &str, network: Network) {
@@ -563,6 +569,15 @@ fn cmd_demo(network: Network) {
         println!("WARNING: Address prefix mismatch!");
     }
 
+    // Round-trip through WIF: importing the key must yield the same address
+    let wif_str = wif::encode_wif(&secret_key, network, true);
+    println!("WIF: {}", wif_str);
+    match DogeAddress::from_wif(&wif_str) {
+        Ok((imported, _)) if imported == address => println!("SUCCESS: WIF import matches address."),
+        Ok(_) => println!("WARNING: WIF import produced a different address!"),
+        Err(e) => println!("WARNING: WIF import failed: {e}"),
+    }
+
     // Phase 2: Mock Data
     println!("\n--- Phase 2: Mock Data ---");
     let mock_txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
@@ -576,7 +591,7 @@ fn cmd_demo(network: Network) {
     builder.add_input(mock_txid, mock_vout);
 
     let amount = 50 * 100_000_000;
-    builder.add_output(&address, amount);
+    builder.add_output(&address, amount).expect("non-zero amount");
 
     let tx = builder.clone().build();
     println!("Transaction Constructed!");
@@ -601,7 +616,7 @@ fn cmd_demo(network: Network) {
         .into_script();
 
     println!("Signing Input 0...");
-    builder.sign_input(0, &secret_key, &prev_script_pubkey);
+    builder.sign_input(0, &secret_key, &prev_script_pubkey).expect("fresh input");
 
     let signed_tx = builder.build();
     let signed_tx_hex = serialize_hex(&signed_tx);
@@ -0,0 +1,107 @@
+//! Local merkle tree computation and inclusion proofs.
+//!
+//! Dogecoin's block merkle tree is identical to Bitcoin's: pairs of double-SHA256
+//! hashes are concatenated and hashed again, duplicating the last element of any
+//! level with an odd number of entries.
+
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::Txid;
+
+fn hash_pair(left: &Txid, right: &Txid) -> Txid {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left.as_byte_array());
+    data[32..].copy_from_slice(right.as_byte_array());
+    Txid::from_raw_hash(sha256d::Hash::hash(&data))
+}
+
+fn next_level(level: &[Txid]) -> Vec<Txid> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Compute the merkle root of a block's txids (in block order).
+///
+/// Returns the all-zero hash for an empty list.
+pub fn compute_merkle_root(txids: &[Txid]) -> Txid {
+    if txids.is_empty() {
+        return Txid::all_zeros();
+    }
+
+    let mut level = txids.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Build the merkle branch proving `target` is part of `txids`.
+///
+/// Returns the sibling hashes from leaf to root and the leaf position, or `None` if
+/// `target` isn't in the list.
+pub fn merkle_proof_for(txids: &[Txid], target: &Txid) -> Option<(Vec<Txid>, u32)> {
+    let position = txids.iter().position(|t| t == target)?;
+
+    let mut branch = Vec::new();
+    let mut level = txids.to_vec();
+    let mut index = position;
+    while level.len() > 1 {
+        let sibling = if index.is_multiple_of(2) { index + 1 } else { index - 1 };
+        branch.push(*level.get(sibling).unwrap_or(&level[index]));
+        level = next_level(&level);
+        index /= 2;
+    }
+
+    Some((branch, position as u32))
+}
+
+/// Verify a merkle branch from `merkle_proof_for` against a block header's merkle root.
+pub fn verify_merkle_proof(txid: &Txid, branch: &[Txid], position: u32, merkle_root: &Txid) -> bool {
+    let mut hash = *txid;
+    let mut index = position;
+    for sibling in branch {
+        hash = if index.is_multiple_of(2) { hash_pair(&hash, sibling) } else { hash_pair(sibling, &hash) };
+        index /= 2;
+    }
+    hash == *merkle_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn txid(n: u8) -> Txid {
+        Txid::from_byte_array([n; 32])
+    }
+
+    #[test]
+    fn test_merkle_root_matches_bitcoin() {
+        for n in 1..=7u8 {
+            let txids: Vec<Txid> = (0..n).map(txid).collect();
+            let expected = bitcoin::merkle_tree::calculate_root(txids.iter().copied()).unwrap();
+            assert_eq!(compute_merkle_root(&txids), expected);
+        }
+        assert_eq!(compute_merkle_root(&[]), Txid::all_zeros());
+
+        // A single-transaction block's merkle root is its coinbase txid
+        let coinbase = Txid::from_str("5b2a3f53f605d62c53e62932dac6925e3d74afa5a4b459745c36d42d0ed26a69").unwrap();
+        assert_eq!(compute_merkle_root(&[coinbase]), coinbase);
+    }
+
+    #[test]
+    fn test_merkle_proof_roundtrip() {
+        let txids: Vec<Txid> = (0..5).map(txid).collect();
+        let root = compute_merkle_root(&txids);
+
+        for t in &txids {
+            let (branch, position) = merkle_proof_for(&txids, t).unwrap();
+            assert_eq!(branch.len(), 3);
+            assert!(verify_merkle_proof(t, &branch, position, &root));
+            assert!(!verify_merkle_proof(&txid(99), &branch, position, &root));
+        }
+
+        assert!(merkle_proof_for(&txids, &txid(99)).is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//! Signed messages (`signmessage` / `verifymessage` compatible).
+//!
+//! The digest is `sha256d("\x19Dogecoin Signed Message:\n" || varint(len) || message)`.
+//! Signatures are 65-byte compact recoverable signatures, base64 encoded, whose header
+//! byte is `27 + recovery_id` (+4 when the key is compressed).
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bitcoin::consensus::encode::VarInt;
+use bitcoin::hashes::{hash160, sha256d, Hash};
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+
+use crate::address::{AddressKind, DogeAddress};
+
+/// Prefix Dogecoin Core hashes in front of every signed message
+pub const MESSAGE_MAGIC: &str = "Dogecoin Signed Message:\n";
+
+/// The digest that gets signed for `message`
+pub fn message_hash(message: &str) -> [u8; 32] {
+    let mut data = Vec::with_capacity(MESSAGE_MAGIC.len() + message.len() + 10);
+    data.extend_from_slice(&bitcoin::consensus::serialize(&VarInt(MESSAGE_MAGIC.len() as u64)));
+    data.extend_from_slice(MESSAGE_MAGIC.as_bytes());
+    data.extend_from_slice(&bitcoin::consensus::serialize(&VarInt(message.len() as u64)));
+    data.extend_from_slice(message.as_bytes());
+    sha256d::Hash::hash(&data).to_byte_array()
+}
+
+/// Sign `message` with a compressed key, returning the base64 signature
+pub fn sign_message(secret_key: &SecretKey, message: &str) -> String {
+    let msg = Message::from_digest(message_hash(message));
+    let (recovery_id, compact) = Secp256k1::signing_only()
+        .sign_ecdsa_recoverable(&msg, secret_key)
+        .serialize_compact();
+
+    let mut sig = Vec::with_capacity(65);
+    sig.push(27 + 4 + recovery_id.to_i32() as u8);
+    sig.extend_from_slice(&compact);
+    BASE64.encode(sig)
+}
+
+/// Check that `signature` over `message` was made by the key behind a P2PKH `address`
+pub fn verify_message(address: &DogeAddress, message: &str, signature: &str) -> bool {
+    if address.kind() != AddressKind::P2pkh {
+        return false;
+    }
+    let Ok(sig) = BASE64.decode(signature) else {
+        return false;
+    };
+    let Some((&header, compact)) = sig.split_first() else {
+        return false;
+    };
+    if sig.len() != 65 || !(27..=34).contains(&header) {
+        return false;
+    }
+
+    let compressed = header >= 31;
+    let Ok(recovery_id) = RecoveryId::from_i32(((header - 27) & 3) as i32) else {
+        return false;
+    };
+    let Ok(recoverable) = RecoverableSignature::from_compact(compact, recovery_id) else {
+        return false;
+    };
+    let msg = Message::from_digest(message_hash(message));
+    let Ok(public_key) = Secp256k1::verification_only().recover_ecdsa(&msg, &recoverable) else {
+        return false;
+    };
+
+    let hash = if compressed {
+        hash160::Hash::hash(&public_key.serialize())
+    } else {
+        hash160::Hash::hash(&public_key.serialize_uncompressed())
+    };
+    hash.as_byte_array() == address.pubkey_hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+    use bitcoin::secp256k1::PublicKey;
+
+    #[test]
+    fn test_sign_and_verify_message() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Mainnet);
+
+        let signature = sign_message(&secret, "hello doge");
+        assert_eq!(BASE64.decode(&signature).unwrap().len(), 65);
+        assert!(verify_message(&address, "hello doge", &signature));
+
+        assert!(!verify_message(&address, "hello dog", &signature));
+        let other = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        assert!(!verify_message(&other, "hello doge", &signature));
+        assert!(!verify_message(&address, "hello doge", "not base64!"));
+    }
+}
@@ -1,6 +1,17 @@
 use std::fmt;
 use std::str::FromStr;
 
+use crate::address::{AddressError, AddressKind, DogeAddress};
+
+/// Confirmations a coinbase output needs before it can be spent (Dogecoin consensus since block 145,000)
+pub const COINBASE_MATURITY: u64 = 240;
+
+/// Upper bound on any amount, in satoshis: Dogecoin Core's `MAX_MONEY` of 10 billion DOGE.
+///
+/// Unlike Bitcoin's 21M, this is not a supply cap (Dogecoin has none); it is a sanity range
+/// that keeps amounts and their sums well inside `u64`.
+pub const MAX_MONEY_SATS: u64 = 10_000_000_000 * 100_000_000;
+
 /// Dogecoin Network Configuration
 /// 
 /// Provides network-specific parameters for address encoding.
@@ -13,29 +24,108 @@ pub enum Network {
     Mainnet,
 }
 
+/// Every network-specific constant, in one table per network
+#[derive(Debug, PartialEq, Eq)]
+pub struct NetworkParams {
+    pub p2pkh_version: u8,
+    pub p2sh_version: u8,
+    pub wif_version: u8,
+    /// P2P message start bytes
+    pub magic: [u8; 4],
+    pub default_port: u16,
+    pub default_rpc_port: u16,
+    /// Dogecoin has no SegWit, so no bech32 prefix on either network
+    pub bech32_hrp: Option<&'static str>,
+    /// Genesis block hash (RPC byte order)
+    pub genesis_hash: &'static str,
+    /// Registered BIP44 coin type (SLIP-44)
+    pub bip44_coin_type: u32,
+    pub recommended_confirmations: u64,
+    pub coinbase_maturity: u64,
+    pub max_money_sats: u64,
+}
+
+pub static MAINNET_PARAMS: NetworkParams = NetworkParams {
+    p2pkh_version: 0x1E, // 'D' prefix
+    p2sh_version: 0x16,  // '9' or 'A' prefix
+    wif_version: 0x9E,
+    magic: [0xc0, 0xc0, 0xc0, 0xc0],
+    default_port: 22556,
+    default_rpc_port: 22555,
+    bech32_hrp: None,
+    genesis_hash: "1a91e3dace36e2be3bf030a65679fe821aa1d6ef92e7c9902eb318182c355691",
+    bip44_coin_type: 3,
+    recommended_confirmations: 40,
+    coinbase_maturity: COINBASE_MATURITY,
+    max_money_sats: MAX_MONEY_SATS,
+};
+
+pub static TESTNET_PARAMS: NetworkParams = NetworkParams {
+    p2pkh_version: 0x71, // 'n' or 'm' prefix
+    p2sh_version: 0xC4,  // '2' prefix
+    wif_version: 0xF1,
+    magic: [0xfc, 0xc1, 0xb7, 0xdc],
+    default_port: 44556,
+    default_rpc_port: 44555,
+    bech32_hrp: None,
+    genesis_hash: "bb0a78264637406b6360aad926284d544d7049f45189db5664f3c4d07350559e",
+    bip44_coin_type: 1, // shared by all test networks
+    recommended_confirmations: 6,
+    coinbase_maturity: COINBASE_MATURITY,
+    max_money_sats: MAX_MONEY_SATS,
+};
+
 impl Network {
-    /// Get the version byte for P2PKH addresses
-    pub fn p2pkh_version_byte(&self) -> u8 {
+    /// Infer the network and address kind from a Base58Check address string
+    pub fn detect_from_address(s: &str) -> Result<(Network, AddressKind), AddressError> {
+        let address = DogeAddress::from_base58(s)?;
+        Ok((address.network, address.kind()))
+    }
+
+    /// Constant table for this network
+    pub fn params(&self) -> &'static NetworkParams {
         match self {
-            Network::Testnet => 0x71, // 'n' or 'm' prefix
-            Network::Mainnet => 0x1E, // 'D' prefix
+            Network::Testnet => &TESTNET_PARAMS,
+            Network::Mainnet => &MAINNET_PARAMS,
         }
     }
 
+    /// Get the version byte for P2PKH addresses
+    pub fn p2pkh_version_byte(&self) -> u8 {
+        self.params().p2pkh_version
+    }
+
     /// Get the version byte for P2SH addresses (for future use)
     pub fn p2sh_version_byte(&self) -> u8 {
-        match self {
-            Network::Testnet => 0xC4, // '2' prefix
-            Network::Mainnet => 0x16, // '9' or 'A' prefix
-        }
+        self.params().p2sh_version
     }
 
     /// Get the WIF (Wallet Import Format) version byte
     pub fn wif_version_byte(&self) -> u8 {
-        match self {
-            Network::Testnet => 0xF1, // WIF testnet
-            Network::Mainnet => 0x9E, // WIF mainnet
-        }
+        self.params().wif_version
+    }
+
+    /// Registered BIP44 coin type (SLIP-44): 3 for Dogecoin, 1 for all test networks
+    pub fn bip44_coin_type(&self) -> u32 {
+        self.params().bip44_coin_type
+    }
+
+    /// Default confirmation depth before treating a payment as final.
+    ///
+    /// Dogecoin blocks come roughly every minute, so mainnet asks for more blocks than
+    /// Bitcoin's customary 6 to cover a similar amount of work. Callers can override it.
+    pub fn recommended_confirmations(&self) -> u64 {
+        self.params().recommended_confirmations
+    }
+
+    /// Number of confirmations before a coinbase output can be spent
+    pub fn coinbase_maturity(&self) -> u64 {
+        self.params().coinbase_maturity
+    }
+
+    /// Largest valid amount in satoshis; see `MAX_MONEY_SATS`
+    pub fn max_money_sats(&self) -> u64 {
+        self.params().max_money_sats
     }
 }
 
@@ -70,6 +160,52 @@ mod tests {
         assert_eq!(Network::Mainnet.p2pkh_version_byte(), 0x1E);
     }
 
+    #[test]
+    fn test_params_tables() {
+        assert_eq!(Network::Mainnet.params(), &MAINNET_PARAMS);
+        assert_eq!(Network::Testnet.params(), &TESTNET_PARAMS);
+        assert_eq!(Network::Mainnet.params().magic, [0xc0, 0xc0, 0xc0, 0xc0]);
+        assert_eq!(Network::Testnet.params().default_port, 44556);
+        assert_eq!(Network::Testnet.params().default_rpc_port, 44555);
+        assert!(Network::Mainnet.params().bech32_hrp.is_none());
+
+        for network in [Network::Testnet, Network::Mainnet] {
+            let params = network.params();
+            assert_eq!(network.p2pkh_version_byte(), params.p2pkh_version);
+            assert_eq!(network.p2sh_version_byte(), params.p2sh_version);
+            assert_eq!(network.wif_version_byte(), params.wif_version);
+            assert_eq!(params.genesis_hash.len(), 64);
+        }
+    }
+
+    #[test]
+    fn test_bip44_coin_type() {
+        assert_eq!(Network::Testnet.bip44_coin_type(), 1);
+        assert_eq!(Network::Mainnet.bip44_coin_type(), 3);
+    }
+
+    #[test]
+    fn test_recommended_confirmations() {
+        assert_eq!(Network::Testnet.recommended_confirmations(), 6);
+        assert_eq!(Network::Mainnet.recommended_confirmations(), 40);
+        assert!(Network::Mainnet.recommended_confirmations() > Network::Testnet.recommended_confirmations());
+    }
+
+    #[test]
+    fn test_coinbase_maturity() {
+        assert_eq!(Network::Testnet.coinbase_maturity(), 240);
+        assert_eq!(Network::Mainnet.coinbase_maturity(), 240);
+    }
+
+    #[test]
+    fn test_max_money_sats() {
+        for network in [Network::Testnet, Network::Mainnet] {
+            // Well above Bitcoin's 21M cap, still far from u64::MAX
+            assert_eq!(network.max_money_sats(), 1_000_000_000_000_000_000);
+            assert!(network.max_money_sats() > 21_000_000 * 100_000_000);
+        }
+    }
+
     #[test]
     fn test_network_from_str() {
         assert_eq!(Network::from_str("testnet").unwrap(), Network::Testnet);
@@ -78,6 +214,18 @@ mod tests {
         assert!(Network::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_detect_from_address() {
+        let main_p2pkh = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet).to_string();
+        assert!(main_p2pkh.starts_with('D'));
+        assert_eq!(Network::detect_from_address(&main_p2pkh).unwrap(), (Network::Mainnet, AddressKind::P2pkh));
+
+        let test_p2sh = DogeAddress::from_script_hash(&[0x11; 20], Network::Testnet).to_string();
+        assert_eq!(Network::detect_from_address(&test_p2sh).unwrap(), (Network::Testnet, AddressKind::P2sh));
+
+        assert!(Network::detect_from_address("DNotAValidAddress").is_err());
+    }
+
     #[test]
     fn test_network_display() {
         assert_eq!(format!("{}", Network::Testnet), "testnet");
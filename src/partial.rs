@@ -0,0 +1,156 @@
+//! Portable envelope for partially signed transactions.
+//!
+//! A `PartialTx` carries a transaction together with the prevouts (value + scriptPubKey)
+//! of its inputs, which is everything another party needs to verify amounts and add
+//! signatures. Binary layout:
+//!
+//! `"DOGEPTX" | version (1 byte) | consensus tx | varint prevout count | consensus TxOut...`
+
+use base64::Engine;
+use bitcoin::consensus::encode::{self, Decodable, Encodable, VarInt};
+use bitcoin::{Transaction, TxOut};
+use std::fmt;
+
+/// Magic prefix identifying the envelope format
+pub const PARTIAL_TX_MAGIC: &[u8; 7] = b"DOGEPTX";
+/// Current envelope version
+pub const PARTIAL_TX_VERSION: u8 = 1;
+
+#[derive(Debug)]
+pub enum PartialTxError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    PrevoutCountMismatch { inputs: usize, prevouts: usize },
+    TrailingBytes(usize),
+    Decode(String),
+    InvalidBase64(String),
+}
+
+impl fmt::Display for PartialTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PartialTxError::InvalidMagic => write!(f, "missing DOGEPTX magic prefix"),
+            PartialTxError::UnsupportedVersion(v) => write!(f, "unsupported envelope version: {v}"),
+            PartialTxError::PrevoutCountMismatch { inputs, prevouts } => {
+                write!(f, "{inputs} inputs but {prevouts} prevouts")
+            }
+            PartialTxError::TrailingBytes(n) => write!(f, "{n} unexpected trailing bytes"),
+            PartialTxError::Decode(e) => write!(f, "decode error: {e}"),
+            PartialTxError::InvalidBase64(e) => write!(f, "invalid base64: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PartialTxError {}
+
+/// A (possibly partially) signed transaction plus the prevout of each input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialTx {
+    pub tx: Transaction,
+    /// Prevouts, one per input and in input order
+    pub prevouts: Vec<TxOut>,
+}
+
+impl PartialTx {
+    pub fn new(tx: Transaction, prevouts: Vec<TxOut>) -> Result<Self, PartialTxError> {
+        if tx.input.len() != prevouts.len() {
+            return Err(PartialTxError::PrevoutCountMismatch { inputs: tx.input.len(), prevouts: prevouts.len() });
+        }
+        Ok(Self { tx, prevouts })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(PARTIAL_TX_MAGIC);
+        out.push(PARTIAL_TX_VERSION);
+        self.tx.consensus_encode(&mut out).expect("in-memory writers don't error");
+        VarInt(self.prevouts.len() as u64)
+            .consensus_encode(&mut out)
+            .expect("in-memory writers don't error");
+        for prevout in &self.prevouts {
+            prevout.consensus_encode(&mut out).expect("in-memory writers don't error");
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PartialTxError> {
+        let rest = bytes.strip_prefix(PARTIAL_TX_MAGIC.as_slice()).ok_or(PartialTxError::InvalidMagic)?;
+        let (&version, mut rest) = rest.split_first().ok_or(PartialTxError::InvalidMagic)?;
+        if version != PARTIAL_TX_VERSION {
+            return Err(PartialTxError::UnsupportedVersion(version));
+        }
+
+        let decode_err = |e: encode::Error| PartialTxError::Decode(e.to_string());
+        let tx = Transaction::consensus_decode(&mut rest).map_err(decode_err)?;
+        let count = VarInt::consensus_decode(&mut rest).map_err(decode_err)?.0 as usize;
+        if count != tx.input.len() {
+            return Err(PartialTxError::PrevoutCountMismatch { inputs: tx.input.len(), prevouts: count });
+        }
+
+        let mut prevouts = Vec::with_capacity(count);
+        for _ in 0..count {
+            prevouts.push(TxOut::consensus_decode(&mut rest).map_err(decode_err)?);
+        }
+        if !rest.is_empty() {
+            return Err(PartialTxError::TrailingBytes(rest.len()));
+        }
+
+        Ok(Self { tx, prevouts })
+    }
+
+    /// Base64 of `to_bytes`, convenient for pasting into chat or issues
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    pub fn from_base64(s: &str) -> Result<Self, PartialTxError> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(s.trim())
+            .map_err(|e| PartialTxError::InvalidBase64(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::DogeAddress;
+    use crate::network::Network;
+    use crate::script::p2pkh_script_pubkey;
+    use crate::transaction::TransactionBuilder;
+    use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use bitcoin::Amount;
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let prev_script = p2pkh_script_pubkey(from.pubkey_hash());
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet), 150_000).unwrap();
+        builder.sign_input(0, &secret, &prev_script).unwrap();
+
+        let prevouts = vec![
+            TxOut { value: Amount::from_sat(100_000), script_pubkey: prev_script.clone() },
+            TxOut { value: Amount::from_sat(200_000), script_pubkey: prev_script },
+        ];
+        let partial = PartialTx::new(builder.build(), prevouts).unwrap();
+
+        let encoded = partial.to_base64();
+        let decoded = PartialTx::from_base64(&encoded).unwrap();
+        assert_eq!(decoded, partial);
+        assert!(!decoded.tx.input[0].script_sig.is_empty());
+        assert!(decoded.tx.input[1].script_sig.is_empty());
+
+        assert!(matches!(PartialTx::from_bytes(b"NOTMAGIC"), Err(PartialTxError::InvalidMagic)));
+        let mut wrong_version = partial.to_bytes();
+        wrong_version[7] = 9;
+        assert!(matches!(PartialTx::from_bytes(&wrong_version), Err(PartialTxError::UnsupportedVersion(9))));
+        assert!(matches!(PartialTx::from_base64("!!"), Err(PartialTxError::InvalidBase64(_))));
+        assert!(PartialTx::new(partial.tx.clone(), vec![]).is_err());
+    }
+}
@@ -1,6 +1,18 @@
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::error::Error;
+use std::fmt;
+use std::io::BufReader;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use bitcoin::Transaction;
+
+use crate::address::DogeAddress;
+use crate::network::Network;
+use crate::signer::Signer;
+use crate::transaction::{TransactionBuilder, DUST_LIMIT};
 
 /// JSON-RPC Client for Dogecoin Node Communication
 /// 
@@ -9,6 +21,8 @@ pub struct DogeRpcClient {
     url: String,
     client: reqwest::blocking::Client,
     auth: Option<(String, String)>,
+    user_agent: String,
+    timeout: Option<Duration>,
 }
 
 /// JSON-RPC Request structure
@@ -25,17 +39,27 @@ struct RpcRequest {
 struct RpcResponse {
     result: Option<Value>,
     error: Option<RpcError>,
-    #[allow(dead_code)]
     id: u64,
 }
 
-/// JSON-RPC Error structure
+/// JSON-RPC error code for a method the node doesn't implement
+pub const RPC_METHOD_NOT_FOUND: i32 = -32601;
+
+/// An error the node returned for a call; downcast a call's error to this to read the code
 #[derive(Deserialize, Debug)]
-struct RpcError {
-    code: i32,
-    message: String,
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
 }
 
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RPC Error {}: {}", self.code, self.message)
+    }
+}
+
+impl Error for RpcError {}
+
 /// UTXO Information
 #[derive(Debug, Clone)]
 pub struct UtxoInfo {
@@ -44,6 +68,121 @@ pub struct UtxoInfo {
     pub value: u64, // in satoshis
     pub script_pubkey: String,
     pub confirmations: u64,
+    pub is_coinbase: bool,
+}
+
+/// Mining/difficulty summary from `getmininginfo`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MiningInfo {
+    pub blocks: u64,
+    pub difficulty: f64,
+    pub networkhashps: f64,
+    pub chain: String,
+}
+
+/// Fee and size of an unconfirmed parent, from `getmempoolentry`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageInfo {
+    /// Parent fee in satoshis
+    pub fee: u64,
+    pub vsize: u64,
+    /// Parent fee rate in sat/byte
+    pub fee_rate: f64,
+}
+
+impl PackageInfo {
+    /// Fee a child of `child_vsize` bytes must pay to bring parent and child
+    /// together to `target_rate` sat/byte (0 if the parent already pays enough)
+    pub fn child_fee_for(&self, target_rate: u64, child_vsize: u64) -> u64 {
+        (self.vsize + child_vsize).saturating_mul(target_rate).saturating_sub(self.fee)
+    }
+}
+
+/// Dogecoin Core's default `-limitancestorcount` / `-limitdescendantcount`
+pub const MEMPOOL_PACKAGE_COUNT_LIMIT: u64 = 25;
+/// Dogecoin Core's default `-limitancestorsize` / `-limitdescendantsize`, in bytes
+pub const MEMPOOL_PACKAGE_SIZE_LIMIT: u64 = 101_000;
+/// Size of the smallest useful CPFP child (one P2PKH input, one P2PKH output)
+const MIN_CPFP_CHILD_SIZE: u64 = 192;
+
+/// How far an unconfirmed transaction's package is from the mempool limits, from `check_pinning`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinningReport {
+    pub size: u64,
+    /// Own fee in satoshis
+    pub fee: u64,
+    /// Counts and sizes include the transaction itself
+    pub ancestor_count: u64,
+    pub ancestor_size: u64,
+    pub descendant_count: u64,
+    pub descendant_size: u64,
+    /// Fees of the transaction and all its descendants, in satoshis. A BIP125
+    /// replacement evicts them all and must pay more than this.
+    pub descendant_fees: u64,
+}
+
+impl PinningReport {
+    /// Even a minimal child would break an ancestor or descendant limit, so CPFP can't work
+    pub fn cpfp_blocked(&self) -> bool {
+        self.descendant_count >= MEMPOOL_PACKAGE_COUNT_LIMIT
+            || self.ancestor_count >= MEMPOOL_PACKAGE_COUNT_LIMIT
+            || self.descendant_size + MIN_CPFP_CHILD_SIZE > MEMPOOL_PACKAGE_SIZE_LIMIT
+            || self.ancestor_size + MIN_CPFP_CHILD_SIZE > MEMPOOL_PACKAGE_SIZE_LIMIT
+    }
+
+    /// Descendants at least as large as the transaction itself pay a lower fee rate,
+    /// so a replacement has to outbid a lot of cheap bulk it gets nothing for
+    pub fn rbf_pinned(&self) -> bool {
+        let (desc_size, desc_fees) = (
+            self.descendant_size.saturating_sub(self.size),
+            self.descendant_fees.saturating_sub(self.fee),
+        );
+        desc_size >= self.size && (desc_fees as u128) * (self.size as u128) < (self.fee as u128) * (desc_size as u128)
+    }
+
+    /// Fee bumping is likely to fail with either method
+    pub fn at_risk(&self) -> bool {
+        self.cpfp_blocked() || self.rbf_pinned()
+    }
+}
+
+/// Node fee policy from `getnetworkinfo`, in sat/byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayFees {
+    /// Minimum fee rate for relay (`relayfee`)
+    pub relay_fee: u64,
+    /// Minimum fee-rate increase for a replacement (`incrementalfee`)
+    pub incremental_fee: u64,
+}
+
+/// One transaction from verbose `getrawmempool` (Dogecoin Core 1.14 fields)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MempoolEntry {
+    pub size: u64,
+    /// Fee in DOGE
+    #[serde(default)]
+    pub fee: f64,
+    pub time: u64,
+    pub height: u64,
+    /// Unconfirmed parents of this transaction
+    #[serde(default)]
+    pub depends: Vec<String>,
+}
+
+/// Entries parsed ahead of the consumer by `stream_raw_mempool`
+const MEMPOOL_STREAM_BUFFER: usize = 256;
+
+/// Iterator over verbose `getrawmempool` entries, see `DogeRpcClient::stream_raw_mempool`
+pub struct MempoolStream {
+    entries: mpsc::Receiver<Result<(String, MempoolEntry), String>>,
+}
+
+impl Iterator for MempoolStream {
+    type Item = Result<(String, MempoolEntry), Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.recv().ok().map(|entry| entry.map_err(Into::into))
+    }
 }
 
 /// Broadcast Result
@@ -69,31 +208,123 @@ impl DogeRpcClient {
             url: url.to_string(),
             client: reqwest::blocking::Client::new(),
             auth,
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
+            timeout: None,
         }
     }
 
+    /// Set a default timeout for every RPC call (reqwest's default applies otherwise)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Override the User-Agent header sent with every request
+    pub fn with_user_agent(mut self, ua: &str) -> Self {
+        self.user_agent = ua.to_string();
+        self
+    }
+
     /// Send a JSON-RPC request
     fn call(&self, method: &str, params: Vec<Value>) -> Result<Value, Box<dyn Error>> {
+        self.call_with_timeout(method, params, self.timeout)
+    }
+
+    /// Send a JSON-RPC request, overriding the client timeout for this call only
+    fn call_with_timeout(&self, method: &str, params: Vec<Value>, timeout: Option<Duration>) -> Result<Value, Box<dyn Error>> {
+        self.send_call(method, params, 1, timeout)
+    }
+
+    /// Send a JSON-RPC request with an explicit id.
+    ///
+    /// Useful behind logging proxies or load balancers that correlate requests and
+    /// responses by id. Errors if the response carries a different id.
+    pub fn call_with_id(&self, method: &str, params: Vec<Value>, id: u64) -> Result<Value, Box<dyn Error>> {
+        self.send_call(method, params, id, self.timeout)
+    }
+
+    /// Send several calls in one JSON-RPC batch request.
+    ///
+    /// Calls get sequential ids starting at 1; results are returned in call order
+    /// regardless of the order the node answers in. Per-call RPC errors are kept
+    /// in the individual results.
+    pub fn call_batch(&self, calls: &[(&str, Vec<Value>)]) -> Result<Vec<Result<Value, String>>, Box<dyn Error>> {
+        let requests: Vec<RpcRequest> = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (method, params))| RpcRequest {
+                jsonrpc: "2.0",
+                id: i as u64 + 1,
+                method: method.to_string(),
+                params: params.clone(),
+            })
+            .collect();
+
+        let responses: Vec<RpcResponse> = self.post(&requests, self.timeout).send()?.json()?;
+
+        let mut results: Vec<Option<Result<Value, String>>> = vec![None; calls.len()];
+        for response in responses {
+            let slot = response
+                .id
+                .checked_sub(1)
+                .and_then(|i| results.get_mut(i as usize))
+                .ok_or_else(|| format!("Unexpected response id {}", response.id))?;
+            *slot = Some(response_result(response).map_err(|e| e.to_string()));
+        }
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| r.ok_or_else(|| format!("Missing response for id {}", i + 1).into()))
+            .collect()
+    }
+
+    fn send_call(&self, method: &str, params: Vec<Value>, id: u64, timeout: Option<Duration>) -> Result<Value, Box<dyn Error>> {
+        response_result(self.send_request(method, params, id, timeout)?)
+    }
+
+    /// Send a JSON-RPC request whose result may legitimately be `null` (e.g. `gettxout`)
+    fn call_optional(&self, method: &str, params: Vec<Value>) -> Result<Option<Value>, Box<dyn Error>> {
+        let response = self.send_request(method, params, 1, self.timeout)?;
+        if let Some(error) = response.error {
+            return Err(Box::new(error));
+        }
+        Ok(response.result)
+    }
+
+    fn send_request(&self, method: &str, params: Vec<Value>, id: u64, timeout: Option<Duration>) -> Result<RpcResponse, Box<dyn Error>> {
         let request = RpcRequest {
             jsonrpc: "2.0",
-            id: 1,
+            id,
             method: method.to_string(),
             params,
         };
 
-        let mut req_builder = self.client.post(&self.url).json(&request);
+        let response: RpcResponse = self.post(&request, timeout).send()?.json()?;
 
-        if let Some((ref user, ref pass)) = self.auth {
-            req_builder = req_builder.basic_auth(user, Some(pass));
+        if response.id != id {
+            return Err(format!("RPC response id {} does not match request id {}", response.id, id).into());
         }
 
-        let response: RpcResponse = req_builder.send()?.json()?;
+        Ok(response)
+    }
 
-        if let Some(error) = response.error {
-            return Err(format!("RPC Error {}: {}", error.code, error.message).into());
+    fn post<T: Serialize>(&self, body: &T, timeout: Option<Duration>) -> reqwest::blocking::RequestBuilder {
+        let mut req_builder = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .json(body);
+
+        if let Some(t) = timeout {
+            req_builder = req_builder.timeout(t);
+        }
+
+        if let Some((ref user, ref pass)) = self.auth {
+            req_builder = req_builder.basic_auth(user, Some(pass));
         }
 
-        response.result.ok_or_else(|| "Empty result from RPC".into())
+        req_builder
     }
 
     /// Fetch UTXO details from a transaction
@@ -133,15 +364,235 @@ impl DogeRpcClient {
             .and_then(|c| c.as_u64())
             .unwrap_or(0);
 
+        // A coinbase transaction has a single input carrying a "coinbase" field instead of a prevout
+        let is_coinbase = tx_result
+            .get("vin")
+            .and_then(|v| v.as_array())
+            .and_then(|v| v.first())
+            .map(|i| i.get("coinbase").is_some())
+            .unwrap_or(false);
+
         Ok(UtxoInfo {
             txid: txid.to_string(),
             vout,
             value: value_satoshis,
             script_pubkey: script_pubkey.to_string(),
             confirmations,
+            is_coinbase,
         })
     }
 
+    /// Whether `txid:vout` is currently unspent, counting mempool spends (`gettxout`).
+    ///
+    /// Use it to prune a stale UTXO list before coin selection.
+    pub fn is_unspent(&self, txid: &str, vout: u32) -> Result<bool, Box<dyn Error>> {
+        let result = self.call_optional("gettxout", vec![json!(txid), json!(vout), json!(true)])?;
+        Ok(result.is_some())
+    }
+
+    /// Unspent outputs matching output descriptors such as `pkh(<pubkey>)` or `addr(<address>)`,
+    /// via `scantxoutset`.
+    ///
+    /// Scans the UTXO set directly, so it works for watch-only addresses without importing or
+    /// rescanning, and on pruned nodes. Needs Dogecoin Core 1.21+; 1.14 nodes lack the call.
+    /// Only confirmed outputs are found.
+    pub fn scan_txout_set(&self, descriptors: &[&str]) -> Result<Vec<UtxoInfo>, Box<dyn Error>> {
+        let result = self.call("scantxoutset", vec![json!("start"), json!(descriptors)])?;
+        if result.get("success").and_then(|s| s.as_bool()) == Some(false) {
+            return Err("scantxoutset did not complete".into());
+        }
+        let tip = result.get("height").and_then(|h| h.as_u64()).ok_or("No height in scantxoutset result")?;
+        let unspents = result
+            .get("unspents")
+            .and_then(|u| u.as_array())
+            .ok_or("No unspents in scantxoutset result")?;
+
+        unspents
+            .iter()
+            .map(|u| {
+                let txid = u.get("txid").and_then(|t| t.as_str()).ok_or("No txid in scantxoutset entry")?;
+                let vout = u.get("vout").and_then(|v| v.as_u64()).ok_or("No vout in scantxoutset entry")?;
+                let amount = u.get("amount").and_then(|a| a.as_f64()).ok_or("No amount in scantxoutset entry")?;
+                let script_pubkey = u
+                    .get("scriptPubKey")
+                    .and_then(|s| s.as_str())
+                    .ok_or("No scriptPubKey in scantxoutset entry")?;
+                let height = u.get("height").and_then(|h| h.as_u64()).unwrap_or(tip);
+                Ok(UtxoInfo {
+                    txid: txid.to_string(),
+                    vout: vout as u32,
+                    value: (amount * 100_000_000.0).round() as u64,
+                    script_pubkey: script_pubkey.to_string(),
+                    confirmations: (tip + 1).saturating_sub(height),
+                    // Only newer nodes report this
+                    is_coinbase: u.get("coinbase").and_then(|c| c.as_bool()).unwrap_or(false),
+                })
+            })
+            .collect()
+    }
+
+    /// Unspent outputs the node's wallet tracks for `addresses`, including unconfirmed ones,
+    /// via `listunspent`. Addresses must be in the wallet or imported as watch-only.
+    pub fn list_unspent(&self, addresses: &[&str]) -> Result<Vec<UtxoInfo>, Box<dyn Error>> {
+        let result = self.call("listunspent", vec![json!(0), json!(9_999_999), json!(addresses)])?;
+        let unspents = result.as_array().ok_or("listunspent did not return an array")?;
+
+        unspents
+            .iter()
+            .map(|u| {
+                let txid = u.get("txid").and_then(|t| t.as_str()).ok_or("No txid in listunspent entry")?;
+                let vout = u.get("vout").and_then(|v| v.as_u64()).ok_or("No vout in listunspent entry")?;
+                let amount = u.get("amount").and_then(|a| a.as_f64()).ok_or("No amount in listunspent entry")?;
+                let script_pubkey = u
+                    .get("scriptPubKey")
+                    .and_then(|s| s.as_str())
+                    .ok_or("No scriptPubKey in listunspent entry")?;
+                Ok(UtxoInfo {
+                    txid: txid.to_string(),
+                    vout: vout as u32,
+                    value: (amount * 100_000_000.0).round() as u64,
+                    script_pubkey: script_pubkey.to_string(),
+                    confirmations: u.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0),
+                    is_coinbase: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Confirmations of a transaction (0 while it's in the mempool)
+    pub fn get_confirmations(&self, txid: &str) -> Result<u64, Box<dyn Error>> {
+        let tx_result = self.call("getrawtransaction", vec![json!(txid), json!(true)])?;
+        Ok(tx_result.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0))
+    }
+
+    /// Whether a transaction has reached `min_confirmations`, defaulting to
+    /// `network.recommended_confirmations()` when `None`
+    pub fn is_confirmed(&self, txid: &str, min_confirmations: Option<u64>, network: Network) -> Result<bool, Box<dyn Error>> {
+        let required = min_confirmations.unwrap_or_else(|| network.recommended_confirmations());
+        Ok(self.get_confirmations(txid)? >= required)
+    }
+
+    /// Fee, size and fee rate of an unconfirmed parent, for sizing a CPFP child
+    pub fn get_cpfp_package_info(&self, parent_txid: &str) -> Result<PackageInfo, Box<dyn Error>> {
+        let entry = self.call("getmempoolentry", vec![json!(parent_txid)])?;
+        Self::package_info(&entry)
+    }
+
+    /// Own fee and size from a `getmempoolentry` result
+    fn package_info(entry: &Value) -> Result<PackageInfo, Box<dyn Error>> {
+        // Newer nodes report `vsize` and `fees.base`; Dogecoin Core 1.14 only `size` and `fee`
+        let vsize = entry
+            .get("vsize")
+            .or_else(|| entry.get("size"))
+            .and_then(|v| v.as_u64())
+            .ok_or("No size in getmempoolentry result")?;
+        let fee_doge = entry
+            .get("fees")
+            .and_then(|f| f.get("base"))
+            .or_else(|| entry.get("fee"))
+            .and_then(|f| f.as_f64())
+            .ok_or("No fee in getmempoolentry result")?;
+        let fee = (fee_doge * 100_000_000.0).round() as u64;
+
+        Ok(PackageInfo {
+            fee,
+            vsize,
+            fee_rate: if vsize == 0 { 0.0 } else { fee as f64 / vsize as f64 },
+        })
+    }
+
+    /// Build and sign a child spending `parent_txid:vout` to `to`, paying enough fee to
+    /// lift parent and child together to `target_rate` sat/byte (CPFP).
+    ///
+    /// The spent output must pay to the P2PKH address of `signer`'s key. The child never
+    /// pays less than `target_rate` on its own size, even if the parent already pays enough.
+    pub fn cpfp(
+        &self,
+        parent_txid: &str,
+        vout: u32,
+        signer: &dyn Signer,
+        target_rate: u64,
+        to: &DogeAddress,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        let utxo = self.fetch_utxo(parent_txid, vout)?;
+        let own_script = DogeAddress::from_pubkey(&signer.public_key(), to.network).script_pubkey();
+        if utxo.script_pubkey != hex::encode(own_script.as_bytes()) {
+            return Err(format!("{parent_txid}:{vout} does not pay to the signing key").into());
+        }
+        let package = self.get_cpfp_package_info(parent_txid)?;
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input_with_prevout(parent_txid, vout, &own_script);
+        builder.add_output(to, utxo.value)?;
+        let child_vsize = builder.estimate_vsize() as u64;
+        let fee = package.child_fee_for(target_rate, child_vsize).max(child_vsize * target_rate);
+        let value = utxo
+            .value
+            .checked_sub(fee)
+            .filter(|v| *v >= DUST_LIMIT)
+            .ok_or_else(|| format!("output of {} sats can't cover a {fee} sat CPFP fee", utxo.value))?;
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input_with_prevout(parent_txid, vout, &own_script);
+        builder.add_output(to, value)?;
+        builder.sign_input(0, signer, &own_script)?;
+        Ok(builder.build())
+    }
+
+    /// Check whether an unconfirmed transaction's descendants (or ancestors) could block
+    /// fee bumping, from `getmempoolentry`.
+    ///
+    /// CPFP fails once the package hits Dogecoin Core's default limits of
+    /// `MEMPOOL_PACKAGE_COUNT_LIMIT` transactions or `MEMPOOL_PACKAGE_SIZE_LIMIT` bytes; RBF
+    /// gets expensive when a large, low-fee descendant package must be outbid as well.
+    pub fn check_pinning(&self, txid: &str) -> Result<PinningReport, Box<dyn Error>> {
+        let entry = self.call("getmempoolentry", vec![json!(txid)])?;
+        let field = |name: &str| entry.get(name).and_then(|v| v.as_u64());
+        let required = |name: &str| field(name).ok_or_else(|| format!("No {name} in getmempoolentry result"));
+        let package = Self::package_info(&entry)?;
+
+        // Dogecoin Core 1.14 reports `descendantfees` in satoshis; newer nodes `fees.descendant` in DOGE
+        let descendant_fees = match entry.get("fees").and_then(|f| f.get("descendant")).and_then(|f| f.as_f64()) {
+            Some(doge) => (doge * 100_000_000.0).round() as u64,
+            None => required("descendantfees")?,
+        };
+
+        Ok(PinningReport {
+            size: package.vsize,
+            fee: package.fee,
+            ancestor_count: required("ancestorcount")?,
+            ancestor_size: required("ancestorsize")?,
+            descendant_count: required("descendantcount")?,
+            descendant_size: required("descendantsize")?,
+            descendant_fees,
+        })
+    }
+
+    /// Stream verbose `getrawmempool` as `(txid, entry)` pairs.
+    ///
+    /// The response is parsed incrementally on a background thread, a bounded number of
+    /// entries ahead of the consumer, so a busy mempool is never held in memory whole.
+    /// An RPC or parse error is yielded as the last item. Dropping the iterator stops the parse.
+    pub fn stream_raw_mempool(&self) -> Result<MempoolStream, Box<dyn Error>> {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            id: 1,
+            method: "getrawmempool".to_string(),
+            params: vec![json!(true)],
+        };
+        let response = self.post(&request, self.timeout).send()?;
+
+        let (tx, rx) = mpsc::sync_channel(MEMPOOL_STREAM_BUFFER);
+        std::thread::spawn(move || {
+            let mut de = serde_json::Deserializer::from_reader(BufReader::new(response));
+            if let Err(e) = MempoolResponseSeed(&tx).deserialize(&mut de) {
+                let _ = tx.send(Err(e.to_string()));
+            }
+        });
+
+        Ok(MempoolStream { entries: rx })
+    }
+
     /// Broadcast a signed transaction to the network
     /// 
     /// # Arguments
@@ -158,6 +609,134 @@ impl DogeRpcClient {
         })
     }
 
+    /// Broadcast with a per-call timeout.
+    ///
+    /// `sendrawtransaction` on a large consolidation can take a while to validate; raise
+    /// the timeout for such transactions instead of changing it for every call.
+    pub fn broadcast_tx_with_timeout(&self, tx_hex: &str, timeout: Duration) -> Result<BroadcastResult, Box<dyn Error>> {
+        let result = self.call_with_timeout("sendrawtransaction", vec![json!(tx_hex)], Some(timeout))?;
+
+        let txid = result
+            .as_str()
+            .ok_or("Expected string txid from sendrawtransaction")?;
+
+        Ok(BroadcastResult {
+            txid: txid.to_string(),
+        })
+    }
+
+    /// Node fee estimate for confirmation within `conf_target` blocks, in sat/byte
+    pub fn estimate_smart_fee(&self, conf_target: u16) -> Result<u64, Box<dyn Error>> {
+        let result = self.call("estimatesmartfee", vec![json!(conf_target)])?;
+        let feerate = result
+            .get("feerate")
+            .and_then(|f| f.as_f64())
+            .ok_or("No feerate in estimatesmartfee result")?;
+        Ok(doge_per_kb_to_sat_per_byte(feerate))
+    }
+
+    /// Current mempool minimum fee (`mempoolminfee` from `getmempoolinfo`), in sat/byte
+    pub fn mempool_min_fee(&self) -> Result<u64, Box<dyn Error>> {
+        let result = self.call("getmempoolinfo", vec![])?;
+        let feerate = result
+            .get("mempoolminfee")
+            .and_then(|f| f.as_f64())
+            .ok_or("No mempoolminfee in getmempoolinfo result")?;
+        Ok(doge_per_kb_to_sat_per_byte(feerate))
+    }
+
+    /// The node's relay fee floor and incremental (replacement) fee, in sat/byte
+    pub fn get_relay_fees(&self) -> Result<RelayFees, Box<dyn Error>> {
+        let result = self.call("getnetworkinfo", vec![])?;
+        let relay_fee = result
+            .get("relayfee")
+            .and_then(|f| f.as_f64())
+            .ok_or("No relayfee in getnetworkinfo result")?;
+        // Older nodes don't report an incremental fee; they use the relay fee for replacements
+        let incremental_fee = result.get("incrementalfee").and_then(|f| f.as_f64()).unwrap_or(relay_fee);
+        Ok(RelayFees {
+            relay_fee: doge_per_kb_to_sat_per_byte(relay_fee),
+            incremental_fee: doge_per_kb_to_sat_per_byte(incremental_fee),
+        })
+    }
+
+    /// Fee rate in sat/byte that never fails.
+    ///
+    /// Tries `estimatesmartfee`, then the mempool minimum fee, then `static_fallback`,
+    /// logging which source was used. Meant for unattended jobs.
+    pub fn robust_fee_rate(&self, conf_target: u16, static_fallback: u64) -> u64 {
+        match self.estimate_smart_fee(conf_target) {
+            Ok(rate) if rate > 0 => {
+                log::info!("fee rate {rate} sat/byte from estimatesmartfee");
+                return rate;
+            }
+            Ok(_) => log::warn!("estimatesmartfee returned a zero fee rate"),
+            Err(e) => log::warn!("estimatesmartfee failed: {e}"),
+        }
+
+        match self.mempool_min_fee() {
+            Ok(rate) if rate > 0 => {
+                log::info!("fee rate {rate} sat/byte from mempool minimum fee");
+                return rate;
+            }
+            Ok(_) => log::warn!("mempool minimum fee is zero"),
+            Err(e) => log::warn!("getmempoolinfo failed: {e}"),
+        }
+
+        log::info!("fee rate {static_fallback} sat/byte from static fallback");
+        static_fallback
+    }
+
+    /// Decode a raw transaction with the node (`decoderawtransaction`)
+    pub fn decode_raw_transaction(&self, tx_hex: &str) -> Result<Value, Box<dyn Error>> {
+        self.call("decoderawtransaction", vec![json!(tx_hex)])
+    }
+
+    /// Total satoshis ever received by a wallet or watch-only `address` in transactions with
+    /// at least `min_conf` confirmations (`getreceivedbyaddress`). Spends don't reduce it.
+    pub fn get_received_by_address(&self, address: &str, min_conf: u64) -> Result<u64, Box<dyn Error>> {
+        let result = self.call("getreceivedbyaddress", vec![json!(address), json!(min_conf)])?;
+        let amount = result.as_f64().ok_or("Expected amount from getreceivedbyaddress")?;
+        Ok((amount * 100_000_000.0).round() as u64)
+    }
+
+    /// Whether `address` is in the node's wallet, as a key or watch-only import.
+    ///
+    /// Uses `getaddressinfo`, or `validateaddress` on nodes that lack it (Dogecoin Core 1.14).
+    pub fn is_wallet_address(&self, address: &str) -> Result<bool, Box<dyn Error>> {
+        let info = match self.call("getaddressinfo", vec![json!(address)]) {
+            Ok(info) => info,
+            Err(e) if e.downcast_ref::<RpcError>().is_some_and(|e| e.code == RPC_METHOD_NOT_FOUND) => {
+                self.call("validateaddress", vec![json!(address)])?
+            }
+            Err(e) => return Err(e),
+        };
+        let flag = |key: &str| info.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        Ok(flag("ismine") || flag("iswatchonly"))
+    }
+
+    /// New address from the node's wallet (`getnewaddress`), optionally labelled
+    pub fn get_new_address(&self, label: Option<&str>) -> Result<String, Box<dyn Error>> {
+        let params = label.map(|l| vec![json!(l)]).unwrap_or_default();
+        let result = self.call("getnewaddress", params)?;
+        Ok(result.as_str().ok_or("Expected string address from getnewaddress")?.to_string())
+    }
+
+    /// WIF private key of a node-wallet address (`dumpprivkey`), for signing with this library
+    pub fn dump_privkey(&self, address: &str) -> Result<String, Box<dyn Error>> {
+        let result = self.call("dumpprivkey", vec![json!(address)])?;
+        Ok(result.as_str().ok_or("Expected string WIF from dumpprivkey")?.to_string())
+    }
+
+    /// Adjust the fee the local node's mempool and block template credit `txid` with
+    /// (`prioritisetransaction`). Nothing is relayed; peers still see the real fee.
+    ///
+    /// Dogecoin Core 1.14 still takes the legacy priority delta, which is sent as 0.
+    pub fn prioritise_transaction(&self, txid: &str, fee_delta_sats: i64) -> Result<bool, Box<dyn Error>> {
+        let result = self.call("prioritisetransaction", vec![json!(txid), json!(0), json!(fee_delta_sats)])?;
+        Ok(result.as_bool().ok_or("Expected boolean from prioritisetransaction")?)
+    }
+
     /// Get blockchain info (useful for testing connection)
     pub fn get_blockchain_info(&self) -> Result<Value, Box<dyn Error>> {
         self.call("getblockchaininfo", vec![])
@@ -167,11 +746,113 @@ impl DogeRpcClient {
     pub fn get_network_info(&self) -> Result<Value, Box<dyn Error>> {
         self.call("getnetworkinfo", vec![])
     }
+
+    /// Get mining info (block height, difficulty, network hashrate)
+    ///
+    /// Dogecoin is merged-mined with Litecoin, so these figures reflect the shared Scrypt hashrate.
+    pub fn get_mining_info(&self) -> Result<MiningInfo, Box<dyn Error>> {
+        let result = self.call("getmininginfo", vec![])?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+type MempoolSender = mpsc::SyncSender<Result<(String, MempoolEntry), String>>;
+
+/// Walks a `getrawmempool` response envelope, streaming `result` into the channel
+struct MempoolResponseSeed<'a>(&'a MempoolSender);
+
+impl<'de> DeserializeSeed<'de> for MempoolResponseSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MempoolResponseSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON-RPC response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut streamed = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "result" => streamed = map.next_value_seed(MempoolEntriesSeed(self.0))?,
+                "error" => {
+                    if let Some(error) = map.next_value::<Option<RpcError>>()? {
+                        return Err(de::Error::custom(format!("RPC Error {}: {}", error.code, error.message)));
+                    }
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !streamed {
+            return Err(de::Error::custom("Empty result from RPC"));
+        }
+        Ok(())
+    }
+}
+
+/// Sends each `txid: entry` pair of the `result` object as soon as it's parsed.
+/// Yields `false` for a `null` result.
+struct MempoolEntriesSeed<'a>(&'a MempoolSender);
+
+impl<'de> DeserializeSeed<'de> for MempoolEntriesSeed<'_> {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MempoolEntriesSeed<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of txid to mempool entry")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<bool, A::Error> {
+        while let Some(entry) = map.next_entry::<String, MempoolEntry>()? {
+            if self.0.send(Ok(entry)).is_err() {
+                return Err(de::Error::custom("mempool stream dropped"));
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn response_result(response: RpcResponse) -> Result<Value, Box<dyn Error>> {
+    if let Some(error) = response.error {
+        return Err(Box::new(error));
+    }
+
+    response.result.ok_or_else(|| "Empty result from RPC".into())
+}
+
+/// Convert a DOGE/kB rate (as reported by the node) into sat/byte, rounding up
+fn doge_per_kb_to_sat_per_byte(doge_per_kb: f64) -> u64 {
+    let sat_per_kb = (doge_per_kb * 100_000_000.0).round() as u64;
+    sat_per_kb.div_ceil(1000)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{mock_server, mock_server_delayed};
+
+    fn rpc_ok(result: Value) -> String {
+        json!({ "result": result, "error": null, "id": 1 }).to_string()
+    }
 
     #[test]
     fn test_rpc_client_creation() {
@@ -185,4 +866,373 @@ mod tests {
         let client = DogeRpcClient::new("http://localhost:44555", None, None);
         assert!(client.auth.is_none());
     }
+
+    #[test]
+    fn test_is_confirmed_defaults_to_network() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let (url, _requests) = mock_server(vec![
+            rpc_ok(json!({ "txid": txid, "confirmations": 10 })),
+            rpc_ok(json!({ "txid": txid, "confirmations": 10 })),
+            rpc_ok(json!({ "txid": txid, "confirmations": 10 })),
+            rpc_ok(json!({ "txid": txid })),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert!(client.is_confirmed(txid, None, Network::Testnet).unwrap());
+        assert!(!client.is_confirmed(txid, None, Network::Mainnet).unwrap());
+        assert!(client.is_confirmed(txid, Some(10), Network::Mainnet).unwrap());
+        assert_eq!(client.get_confirmations(txid).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_scan_txout_set() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let script = "76a914111111111111111111111111111111111111111188ac";
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({
+                "success": true,
+                "txouts": 1000,
+                "height": 5_000_000,
+                "unspents": [
+                    { "txid": txid, "vout": 1, "scriptPubKey": script, "desc": "addr(...)", "amount": 12.5, "height": 4_999_991 },
+                    { "txid": txid, "vout": 0, "scriptPubKey": script, "amount": 0.00000001, "height": 5_000_000, "coinbase": true }
+                ],
+                "total_amount": 12.50000001
+            })),
+            rpc_err(-32601, "Method not found"),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+        let descriptor = "pkh(02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9)";
+
+        let utxos = client.scan_txout_set(&[descriptor]).unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert_eq!((utxos[0].vout, utxos[0].value, utxos[0].confirmations), (1, 1_250_000_000, 10));
+        assert_eq!(utxos[0].script_pubkey, script);
+        assert!(!utxos[0].is_coinbase);
+        assert_eq!((utxos[1].value, utxos[1].confirmations, utxos[1].is_coinbase), (1, 1, true));
+
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "scantxoutset");
+        assert_eq!(request["params"], json!(["start", [descriptor]]));
+
+        assert!(client.scan_txout_set(&[descriptor]).is_err());
+    }
+
+    #[test]
+    fn test_is_unspent() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({ "bestblock": "00", "confirmations": 3, "value": 1.5 })),
+            rpc_ok(Value::Null),
+            rpc_err(-8, "Invalid parameter"),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert!(client.is_unspent(txid, 0).unwrap());
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "gettxout");
+        assert_eq!(request["params"], json!([txid, 0, true]));
+
+        assert!(!client.is_unspent(txid, 1).unwrap());
+        assert!(client.is_unspent(txid, 2).is_err());
+    }
+
+    #[test]
+    fn test_get_cpfp_package_info() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({ "size": 226, "fee": 0.00226, "time": 1700000000 })),
+            rpc_ok(json!({ "vsize": 200, "fees": { "base": 0.001 } })),
+            rpc_err(-5, "Transaction not in mempool"),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let info = client.get_cpfp_package_info(txid).unwrap();
+        assert_eq!(info, PackageInfo { fee: 226_000, vsize: 226, fee_rate: 1_000.0 });
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getmempoolentry");
+        assert_eq!(request["params"], json!([txid]));
+
+        let info = client.get_cpfp_package_info(txid).unwrap();
+        assert_eq!((info.fee, info.vsize, info.fee_rate), (100_000, 200, 500.0));
+        // Parent + 192-byte child at 1000 sat/byte, minus what the parent already pays
+        assert_eq!(info.child_fee_for(1_000, 192), 292_000);
+        assert_eq!(info.child_fee_for(100, 192), 0);
+
+        assert!(client.get_cpfp_package_info(txid).is_err());
+    }
+
+    #[test]
+    fn test_cpfp() {
+        let parent = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let secret = bitcoin::secp256k1::SecretKey::from_slice(b"12345678901234567890123456789012").unwrap();
+        let from = DogeAddress::from_pubkey(&secret.public_key(&bitcoin::secp256k1::Secp256k1::new()), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let parent_tx = |script: &bitcoin::ScriptBuf| {
+            rpc_ok(json!({
+                "confirmations": 0,
+                "vin": [{ "txid": "aa", "vout": 0 }],
+                "vout": [{ "value": 5.0, "scriptPubKey": { "hex": hex::encode(script.as_bytes()) } }]
+            }))
+        };
+        let (url, _requests) = mock_server(vec![
+            parent_tx(&from.script_pubkey()),
+            rpc_ok(json!({ "size": 226, "fee": 0.00226 })),
+            parent_tx(&to.script_pubkey()),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let child = client.cpfp(parent, 0, &secret, 1_000, &to).unwrap();
+        assert_eq!(child.input[0].previous_output.to_string(), format!("{parent}:0"));
+        assert_eq!(child.output[0].script_pubkey, to.script_pubkey());
+        // The child covers both sizes at 1000 sat/byte, less the 226_000 the parent paid.
+        // Its size is the 1-in/1-out P2PKH estimate: 10 + 148 + 34 bytes.
+        let package = PackageInfo { fee: 226_000, vsize: 226, fee_rate: 1_000.0 };
+        let fee = 500_000_000 - child.output[0].value.to_sat();
+        assert_eq!(fee, package.child_fee_for(1_000, 192));
+        assert_eq!(fee, 192_000);
+        let prevout = bitcoin::TxOut { value: bitcoin::Amount::from_sat(500_000_000), script_pubkey: from.script_pubkey() };
+        crate::interpreter::validate_scripts(&child, &[prevout]).unwrap();
+
+        let err = client.cpfp(parent, 0, &secret, 1_000, &to).unwrap_err();
+        assert!(err.to_string().contains("does not pay to the signing key"));
+    }
+
+    #[test]
+    fn test_check_pinning() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let entry = |descendantcount: u64, descendantsize: u64, descendantfees: u64| {
+            rpc_ok(json!({
+                "size": 226, "fee": 0.00226, "time": 1700000000, "height": 5000000,
+                "descendantcount": descendantcount, "descendantsize": descendantsize, "descendantfees": descendantfees,
+                "ancestorcount": 1, "ancestorsize": 226, "ancestorfees": 226000
+            }))
+        };
+        let (url, requests) = mock_server(vec![
+            entry(1, 226, 226_000),
+            // 24 cheap descendants: no room for a child, and a replacement must outbid 50 kB
+            entry(25, 50_226, 226_000 + 5_000_000),
+            entry(3, 2_226, 226_000 + 5_000_000),
+            rpc_ok(json!({ "size": 226 })),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let report = client.check_pinning(txid).unwrap();
+        assert_eq!((report.size, report.fee, report.descendant_fees), (226, 226_000, 226_000));
+        assert!(!report.cpfp_blocked() && !report.rbf_pinned() && !report.at_risk());
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getmempoolentry");
+
+        let report = client.check_pinning(txid).unwrap();
+        assert!(report.cpfp_blocked());
+        assert!(report.rbf_pinned());
+        assert!(report.at_risk());
+
+        // Descendants paying a higher rate than the parent help rather than pin
+        let report = client.check_pinning(txid).unwrap();
+        assert!(!report.at_risk());
+
+        assert!(client.check_pinning(txid).is_err());
+    }
+
+    #[test]
+    fn test_stream_raw_mempool() {
+        let parent = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let child = "0e3e2357e806b6cdb1f70b54c3a3a17b6714ee1f0e68bebb44a74b1efd512098";
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({
+                parent: { "size": 226, "fee": 0.00226, "time": 1700000000, "height": 5000000, "depends": [] },
+                child: { "size": 191, "fee": 0.5, "time": 1700000010, "height": 5000000, "depends": [parent] }
+            })),
+            rpc_err(-1, "mempool unavailable"),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let entries: Vec<(String, MempoolEntry)> = client.stream_raw_mempool().unwrap().map(Result::unwrap).collect();
+        assert_eq!(entries.len(), 2);
+        let (_, entry) = entries.iter().find(|(txid, _)| txid == child).unwrap();
+        assert_eq!(entry.size, 191);
+        assert_eq!(entry.fee, 0.5);
+        assert_eq!(entry.depends, vec![parent.to_string()]);
+
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getrawmempool");
+        assert_eq!(request["params"], json!([true]));
+
+        let mut stream = client.stream_raw_mempool().unwrap();
+        assert!(stream.next().unwrap().unwrap_err().to_string().contains("mempool unavailable"));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_node_wallet_address_and_key() {
+        use crate::address::DogeAddress;
+
+        let wif = "cejxntqoC3o8qiC8HG8DrwoNyiRDBrMCEU8QrUVpLKdXsGy8LpTM";
+        let (expected, _) = DogeAddress::from_wif(wif).unwrap();
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!(expected.to_string())),
+            rpc_ok(json!(wif)),
+            rpc_ok(json!(expected.to_string())),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let address = client.get_new_address(Some("demo")).unwrap();
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getnewaddress");
+        assert_eq!(request["params"], json!(["demo"]));
+        let parsed = DogeAddress::from_base58(&address).unwrap();
+        assert_eq!(parsed.network, Network::Testnet);
+
+        let key = client.dump_privkey(&address).unwrap();
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "dumpprivkey");
+        assert_eq!(request["params"], json!([address]));
+        assert_eq!(DogeAddress::from_wif(&key).unwrap().0, parsed);
+
+        client.get_new_address(None).unwrap();
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["params"], json!([]));
+    }
+
+    #[test]
+    fn test_prioritise_transaction() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let (url, requests) = mock_server(vec![rpc_ok(json!(true)), rpc_ok(json!("unexpected"))]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert!(client.prioritise_transaction(txid, -50_000).unwrap());
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "prioritisetransaction");
+        assert_eq!(request["params"], json!([txid, 0, -50_000]));
+
+        assert!(client.prioritise_transaction(txid, 1).is_err());
+    }
+
+    #[test]
+    fn test_get_relay_fees() {
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({ "version": 1140700, "relayfee": 1.0, "incrementalfee": 0.1 })),
+            rpc_ok(json!({ "relayfee": 0.001 })),
+            rpc_ok(json!({ "version": 1140700 })),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert_eq!(client.get_relay_fees().unwrap(), RelayFees { relay_fee: 100_000, incremental_fee: 10_000 });
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getnetworkinfo");
+
+        assert_eq!(client.get_relay_fees().unwrap(), RelayFees { relay_fee: 100, incremental_fee: 100 });
+        assert!(client.get_relay_fees().is_err());
+    }
+
+    #[test]
+    fn test_get_mining_info() {
+        let (url, requests) = mock_server(vec![rpc_ok(json!({
+            "blocks": 5_000_000,
+            "difficulty": 12_345_678.5,
+            "networkhashps": 1.5e15,
+            "chain": "main",
+            "pooledtx": 12
+        }))]);
+
+        let client = DogeRpcClient::new(&url, None, None);
+        let info = client.get_mining_info().unwrap();
+        assert_eq!(info.blocks, 5_000_000);
+        assert_eq!(info.difficulty, 12_345_678.5);
+        assert_eq!(info.networkhashps, 1.5e15);
+        assert_eq!(info.chain, "main");
+
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "getmininginfo");
+    }
+
+    #[test]
+    fn test_user_agent_header() {
+        let (url, requests) = mock_server(vec![rpc_ok(json!({})), rpc_ok(json!({}))]);
+
+        DogeRpcClient::new(&url, None, None).get_network_info().unwrap();
+        let head = requests.recv().unwrap().head.to_ascii_lowercase();
+        assert!(head.contains(&format!("user-agent: {}", crate::DEFAULT_USER_AGENT)));
+
+        DogeRpcClient::new(&url, None, None).with_user_agent("my-app/1.0").get_network_info().unwrap();
+        let head = requests.recv().unwrap().head.to_ascii_lowercase();
+        assert!(head.contains("user-agent: my-app/1.0"));
+    }
+
+    #[test]
+    fn test_broadcast_tx_with_timeout() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let (url, _requests) = mock_server_delayed(vec![rpc_ok(json!(txid)), rpc_ok(json!(txid))], Duration::from_millis(500));
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert!(client.broadcast_tx_with_timeout("00", Duration::from_millis(50)).is_err());
+
+        let result = client.broadcast_tx_with_timeout("00", Duration::from_secs(5)).unwrap();
+        assert_eq!(result.txid, txid);
+    }
+
+    fn rpc_err(code: i32, message: &str) -> String {
+        json!({ "result": null, "error": { "code": code, "message": message }, "id": 1 }).to_string()
+    }
+
+    #[test]
+    fn test_robust_fee_rate_fallbacks() {
+        let (url, requests) = mock_server(vec![
+            rpc_err(-32601, "Method not found"),
+            rpc_err(-1, "mempool unavailable"),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+        assert_eq!(client.robust_fee_rate(6, 1_000), 1_000);
+
+        let first: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(first["method"], "estimatesmartfee");
+        assert_eq!(first["params"], json!([6]));
+
+        let (url, _requests) = mock_server(vec![
+            rpc_ok(json!({ "errors": ["Insufficient data or no feerate found"], "blocks": 6 })),
+            rpc_ok(json!({ "size": 3, "mempoolminfee": 0.005 })),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+        assert_eq!(client.robust_fee_rate(6, 1_000), 500);
+
+        let (url, _requests) = mock_server(vec![rpc_ok(json!({ "feerate": 0.02, "blocks": 2 }))]);
+        let client = DogeRpcClient::new(&url, None, None);
+        assert_eq!(client.robust_fee_rate(2, 1_000), 2_000);
+    }
+
+    #[test]
+    fn test_call_with_id() {
+        let (url, requests) = mock_server(vec![
+            json!({ "result": 7, "error": null, "id": 42 }).to_string(),
+            json!({ "result": 7, "error": null, "id": 1 }).to_string(),
+        ]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        assert_eq!(client.call_with_id("getblockcount", vec![], 42).unwrap(), json!(7));
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["id"], 42);
+
+        assert!(client.call_with_id("getblockcount", vec![], 43).is_err());
+    }
+
+    #[test]
+    fn test_call_batch_sequential_ids() {
+        let (url, requests) = mock_server(vec![json!([
+            { "result": null, "error": { "code": -5, "message": "No such tx" }, "id": 2 },
+            { "result": 100, "error": null, "id": 1 }
+        ])
+        .to_string()]);
+        let client = DogeRpcClient::new(&url, None, None);
+
+        let results = client
+            .call_batch(&[("getblockcount", vec![]), ("getrawtransaction", vec![json!("aa")])])
+            .unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &json!(100));
+        assert!(results[1].as_ref().unwrap_err().contains("No such tx"));
+
+        let request: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        let ids: Vec<u64> = request.as_array().unwrap().iter().map(|r| r["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
 }
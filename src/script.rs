@@ -1,7 +1,11 @@
 use bitcoin::blockdata::opcodes;
 use bitcoin::blockdata::script::Builder as ScriptBuilder;
 use bitcoin::hashes::{hash160, Hash};
-use bitcoin::script::ScriptBuf;
+use bitcoin::script::{Instruction, Script, ScriptBuf};
+
+use crate::address::DogeAddress;
+use crate::network::Network;
+use crate::transaction::AVG_SIG_SIZE;
 
 #[derive(Debug)]
 pub enum ScriptError {
@@ -68,21 +72,155 @@ pub fn multisig_redeem_script(m: u8, pubkeys: &[Vec<u8>]) -> Result<ScriptBuf, S
         .into_script())
 }
 
+/// P2PKH scriptPubKey: OP_DUP OP_HASH160 <pubkey_hash> OP_EQUALVERIFY OP_CHECKSIG
+pub fn p2pkh_script_pubkey(pubkey_hash: &[u8]) -> ScriptBuf {
+    ScriptBuilder::new()
+        .push_opcode(opcodes::all::OP_DUP)
+        .push_opcode(opcodes::all::OP_HASH160)
+        .push_slice(<&bitcoin::script::PushBytes>::try_from(pubkey_hash).expect("valid push bytes"))
+        .push_opcode(opcodes::all::OP_EQUALVERIFY)
+        .push_opcode(opcodes::all::OP_CHECKSIG)
+        .into_script()
+}
+
+/// Redeem script for P2SH-wrapped P2PKH (`sh(pkh(...))`): the plain P2PKH script.
+///
+/// Pay to it with `p2sh_address`; spend with `TransactionBuilder::sign_sh_pkh_input`.
+pub fn sh_pkh_redeem_script(pubkey_hash: &[u8]) -> ScriptBuf {
+    p2pkh_script_pubkey(pubkey_hash)
+}
+
 /// P2SH scriptPubKey: OP_HASH160 <hash160(redeem_script)> OP_EQUAL
 pub fn p2sh_script_pubkey(redeem_script: &ScriptBuf) -> ScriptBuf {
-    let h = hash160::Hash::hash(redeem_script.as_bytes());
+    p2sh_script_pubkey_from_hash(&redeem_script_hash160(redeem_script))
+}
+
+/// P2SH scriptPubKey from an already-computed 20-byte script hash
+pub fn p2sh_script_pubkey_from_hash(script_hash: &[u8]) -> ScriptBuf {
     ScriptBuilder::new()
         .push_opcode(opcodes::all::OP_HASH160)
-        .push_slice(<&bitcoin::script::PushBytes>::try_from(h.as_byte_array().as_slice()).expect("valid push bytes"))
+        .push_slice(<&bitcoin::script::PushBytes>::try_from(script_hash).expect("valid push bytes"))
         .push_opcode(opcodes::all::OP_EQUAL)
         .into_script()
 }
 
+/// The P2SH deposit address for a redeem script
+pub fn p2sh_address(redeem_script: &ScriptBuf, network: Network) -> DogeAddress {
+    DogeAddress::from_script_hash(&redeem_script_hash160(redeem_script), network)
+}
+
 pub fn redeem_script_hash160(redeem_script: &ScriptBuf) -> [u8; 20] {
     let h = hash160::Hash::hash(redeem_script.as_bytes());
     *h.as_byte_array()
 }
 
+/// Standard scriptPubKey (or P2SH redeem script) templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pkh,
+    P2pk,
+    /// P2SH with an unknown redeem script
+    P2sh,
+    /// P2SH wrapping an m-of-n multisig redeem script
+    P2shMultisig { m: u8, n: u8 },
+    /// P2SH wrapping a P2PKH redeem script (`sh(pkh(...))`)
+    ShPkh,
+    /// Bare m-of-n multisig
+    Multisig { m: u8, n: u8 },
+    NullData,
+    Unknown,
+}
+
+/// Classify a scriptPubKey. P2SH outputs classify as `P2sh` since the redeem script
+/// isn't known yet; use `classify_redeem_script` once it is.
+pub fn classify(script: &Script) -> ScriptType {
+    if script.is_p2pkh() {
+        ScriptType::P2pkh
+    } else if script.is_p2sh() {
+        ScriptType::P2sh
+    } else if script.is_p2pk() {
+        ScriptType::P2pk
+    } else if script.is_op_return() {
+        ScriptType::NullData
+    } else if let Some((m, n)) = parse_multisig(script) {
+        ScriptType::Multisig { m, n }
+    } else {
+        ScriptType::Unknown
+    }
+}
+
+/// Classify a P2SH input by its redeem script
+pub fn classify_redeem_script(redeem_script: &Script) -> ScriptType {
+    if redeem_script.is_p2pkh() {
+        return ScriptType::ShPkh;
+    }
+    match parse_multisig(redeem_script) {
+        Some((m, n)) => ScriptType::P2shMultisig { m, n },
+        None => ScriptType::P2sh,
+    }
+}
+
+fn small_int(op: opcodes::Opcode) -> Option<u8> {
+    let code = op.to_u8();
+    (opcodes::all::OP_PUSHNUM_1.to_u8()..=opcodes::all::OP_PUSHNUM_16.to_u8())
+        .contains(&code)
+        .then(|| code - opcodes::all::OP_PUSHNUM_1.to_u8() + 1)
+}
+
+fn parse_multisig(script: &Script) -> Option<(u8, u8)> {
+    let ins: Vec<Instruction> = script.instructions().collect::<Result<_, _>>().ok()?;
+    let (first, rest) = ins.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (n_op, keys) = rest.split_last()?;
+    let (Instruction::Op(first), Instruction::Op(n_op), Instruction::Op(last)) = (first, n_op, last) else {
+        return None;
+    };
+    let (m, n) = (small_int(*first)?, small_int(*n_op)?);
+    let keys_ok = keys.iter().all(|k| matches!(k, Instruction::PushBytes(pk) if pk.len() == 33 || pk.len() == 65));
+    (*last == opcodes::all::OP_CHECKMULTISIG && keys_ok && keys.len() == n as usize && m <= n).then_some((m, n))
+}
+
+/// Estimated serialized size of an input spending `script_type`, assuming typical 72-byte signatures.
+///
+/// Roughly 148 bytes for P2PKH, 174 for sh(pkh) and 297 for a P2SH 2-of-3 multisig. P2SH inputs with an
+/// unknown redeem script and non-standard scripts fall back to the P2PKH size.
+pub fn estimated_input_size(script_type: &ScriptType) -> usize {
+    input_size_with_sig(script_type, AVG_SIG_SIZE)
+}
+
+pub(crate) fn input_size_with_sig(script_type: &ScriptType, sig_size: usize) -> usize {
+    let sig_push = 1 + sig_size;
+    let script_sig_len = match *script_type {
+        ScriptType::P2pk => sig_push,
+        ScriptType::Multisig { m, .. } => 1 + m as usize * sig_push,
+        ScriptType::P2shMultisig { m, n } => {
+            let redeem_len = 3 + 34 * n as usize;
+            1 + m as usize * sig_push + push_opcode_len(redeem_len) + redeem_len
+        }
+        // <sig> <pubkey> <25-byte P2PKH redeem script>
+        ScriptType::ShPkh => sig_push + 1 + 33 + 1 + 25,
+        ScriptType::P2pkh | ScriptType::P2sh | ScriptType::NullData | ScriptType::Unknown => sig_push + 1 + 33,
+    };
+    // outpoint (36) + sequence (4) + scriptSig length prefix + scriptSig
+    40 + varint_len(script_sig_len) + script_sig_len
+}
+
+fn push_opcode_len(len: usize) -> usize {
+    match len {
+        0..=75 => 1,
+        76..=0xff => 2,
+        _ => 3,
+    }
+}
+
+fn varint_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,12 +229,56 @@ mod tests {
     fn test_multisig_redeem_script_2of3() {
         let pubkeys = vec![vec![0x02u8; 33], vec![0x03u8; 33], vec![0x02u8; 33]];
         let script = multisig_redeem_script(2, &pubkeys).unwrap();
-        assert!(script.as_bytes().len() > 0);
+        assert!(!script.as_bytes().is_empty());
 
         let p2sh = p2sh_script_pubkey(&script);
-        assert!(p2sh.as_bytes().len() > 0);
+        assert!(!p2sh.as_bytes().is_empty());
 
         let h = redeem_script_hash160(&script);
         assert_eq!(h.len(), 20);
     }
+
+    #[test]
+    fn test_p2sh_address() {
+        let pubkeys = vec![vec![0x02u8; 33], vec![0x03u8; 33], vec![0x02u8; 33]];
+        let redeem = multisig_redeem_script(2, &pubkeys).unwrap();
+
+        let address = p2sh_address(&redeem, Network::Mainnet);
+        assert_eq!(address, DogeAddress::from_script_hash(&redeem_script_hash160(&redeem), Network::Mainnet));
+        assert_eq!(address.script_pubkey(), p2sh_script_pubkey(&redeem));
+        assert!(address.to_string().starts_with(['9', 'A']));
+    }
+
+    #[test]
+    fn test_classify() {
+        let pubkeys = vec![vec![0x02u8; 33], vec![0x03u8; 33], vec![0x02u8; 33]];
+        let redeem = multisig_redeem_script(2, &pubkeys).unwrap();
+
+        assert_eq!(classify(&p2pkh_script_pubkey(&[0x11; 20])), ScriptType::P2pkh);
+        assert_eq!(classify(&p2sh_script_pubkey(&redeem)), ScriptType::P2sh);
+        assert_eq!(classify(&redeem), ScriptType::Multisig { m: 2, n: 3 });
+        assert_eq!(classify_redeem_script(&redeem), ScriptType::P2shMultisig { m: 2, n: 3 });
+        assert_eq!(classify_redeem_script(&p2pkh_script_pubkey(&[0x11; 20])), ScriptType::ShPkh);
+        assert_eq!(classify_redeem_script(&ScriptBuf::from_bytes(vec![0x51])), ScriptType::P2sh);
+        assert_eq!(classify(&ScriptBuf::new()), ScriptType::Unknown);
+    }
+
+    #[test]
+    fn test_estimated_input_size() {
+        let p2pkh = estimated_input_size(&ScriptType::P2pkh);
+        let multisig = estimated_input_size(&ScriptType::P2shMultisig { m: 2, n: 3 });
+        assert_eq!(p2pkh, crate::transaction::P2PKH_INPUT_SIZE);
+        assert_eq!(multisig, 297);
+        assert!(multisig > p2pkh);
+    }
+
+    #[test]
+    fn test_sh_pkh_input_size() {
+        let redeem = sh_pkh_redeem_script(&[0x11; 20]);
+        assert_eq!(classify_redeem_script(&redeem), ScriptType::ShPkh);
+        // The P2PKH scriptSig plus a push of the 25-byte redeem script
+        let sh_pkh = estimated_input_size(&ScriptType::ShPkh);
+        assert_eq!(sh_pkh, estimated_input_size(&ScriptType::P2pkh) + 26);
+        assert_eq!(sh_pkh, 174);
+    }
 }
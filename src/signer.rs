@@ -0,0 +1,92 @@
+//! Signing abstraction so keys can live outside the process (hardware wallets, HSMs).
+//!
+//! `TransactionBuilder::sign_input` only needs the public key and an ECDSA signature
+//! over the sighash, so an external device just has to implement `Signer`.
+
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+/// Produces ECDSA signatures for a single key
+pub trait Signer {
+    /// The public key placed in the scriptSig
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign a 32-byte sighash digest
+    fn sign_ecdsa(&self, msg: &Message) -> Signature;
+}
+
+impl Signer for SecretKey {
+    fn public_key(&self) -> PublicKey {
+        PublicKey::from_secret_key(&Secp256k1::signing_only(), self)
+    }
+
+    fn sign_ecdsa(&self, msg: &Message) -> Signature {
+        Secp256k1::signing_only().sign_ecdsa(msg, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::address::DogeAddress;
+    use crate::network::Network;
+    use crate::transaction::{verify_input, TransactionBuilder};
+
+    /// Stands in for a hardware wallet: holds the key privately and counts requests
+    struct DeviceSigner {
+        key: SecretKey,
+        requests: Cell<usize>,
+    }
+
+    impl Signer for DeviceSigner {
+        fn public_key(&self) -> PublicKey {
+            Signer::public_key(&self.key)
+        }
+
+        fn sign_ecdsa(&self, msg: &Message) -> Signature {
+            self.requests.set(self.requests.get() + 1);
+            Signer::sign_ecdsa(&self.key, msg)
+        }
+    }
+
+    #[test]
+    fn test_external_signer() {
+        let device = DeviceSigner {
+            key: SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap(),
+            requests: Cell::new(0),
+        };
+        let address = DogeAddress::from_pubkey(&device.public_key(), Network::Testnet);
+        let prev_script = address.script_pubkey();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 100_000).unwrap();
+        builder.sign_input(0, &device, &prev_script).unwrap();
+
+        assert_eq!(device.requests.get(), 1);
+        assert!(verify_input(&builder.build(), 0, &prev_script));
+    }
+
+    #[test]
+    fn test_external_signer_cosigns_multisig() {
+        let local = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let device = DeviceSigner { key: SecretKey::from_slice(&[0x02; 32]).unwrap(), requests: Cell::new(0) };
+        let pubkeys = [Signer::public_key(&local), device.public_key()].map(|k| k.serialize().to_vec());
+        let redeem = crate::script::multisig_redeem_script(2, &pubkeys).unwrap();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet), 100_000).unwrap();
+        builder.sign_input_p2sh_multisig(0, &[&local, &device], &redeem).unwrap();
+        assert_eq!(device.requests.get(), 1);
+
+        let tx = builder.build();
+        let prevout = bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(200_000_000),
+            script_pubkey: crate::script::p2sh_script_pubkey(&redeem),
+        };
+        crate::interpreter::validate_scripts(&tx, &[prevout]).unwrap();
+    }
+}
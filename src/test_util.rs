@@ -0,0 +1,61 @@
+//! Shared helpers for unit tests that talk HTTP.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// A request captured by `mock_server`
+pub struct MockRequest {
+    /// Request line and headers
+    pub head: String,
+    pub body: String,
+}
+
+/// Spawn a local HTTP server that answers each request with the next body in `responses`.
+/// Returns the server URL and a receiver yielding the captured requests.
+pub fn mock_server(responses: Vec<String>) -> (String, mpsc::Receiver<MockRequest>) {
+    mock_server_delayed(responses, Duration::ZERO)
+}
+
+/// Like `mock_server`, but waits `delay` before answering each request.
+pub fn mock_server_delayed(responses: Vec<String>, delay: Duration) -> (String, mpsc::Receiver<MockRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        for body in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = v.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+            }
+            let mut request = vec![0u8; content_length];
+            reader.read_exact(&mut request).unwrap();
+            let _ = tx.send(MockRequest { head, body: String::from_utf8(request).unwrap() });
+
+            std::thread::sleep(delay);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            // The client may have timed out and hung up already
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    (url, rx)
+}
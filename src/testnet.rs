@@ -0,0 +1,145 @@
+//! Testnet presets.
+//!
+//! Convenience entry points for developers experimenting on Dogecoin testnet,
+//! wired to the chain.so testnet explorer with testnet-appropriate fee defaults.
+
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use bitcoin::Transaction;
+use std::error::Error;
+
+use crate::address::DogeAddress;
+use crate::coinselect;
+use crate::explorer::{ChainSoClient, ExplorerUtxo};
+use crate::network::Network;
+use crate::transaction::{estimate_p2pkh_size, TransactionBuilder, DUST_LIMIT};
+
+/// Default testnet fee rate in sat/byte (0.01 DOGE/kB)
+pub const TESTNET_FEE_RATE: u64 = 1_000;
+
+/// Sweep every spendable UTXO of a faucet-funded testnet address into `to`.
+///
+/// Fetches UTXOs from chain.so, skips immature coinbase outputs, pays
+/// `TESTNET_FEE_RATE` and signs every input with `secret_key`.
+pub fn sweep_faucet(
+    secret_key: &SecretKey,
+    from: &DogeAddress,
+    to: &DogeAddress,
+    network: Network,
+) -> Result<Transaction, Box<dyn Error>> {
+    if network != Network::Testnet {
+        return Err(format!("sweep_faucet is a testnet preset, got {network}").into());
+    }
+
+    let utxos = ChainSoClient::new().get_tx_unspent(&from.to_string(), network)?;
+    build_sweep(secret_key, from, &utxos, to, network, TESTNET_FEE_RATE)
+}
+
+fn build_sweep(
+    secret_key: &SecretKey,
+    from: &DogeAddress,
+    utxos: &[ExplorerUtxo],
+    to: &DogeAddress,
+    network: Network,
+    fee_rate: u64,
+) -> Result<Transaction, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let public_key = PublicKey::from_secret_key(&secp, secret_key);
+    if DogeAddress::from_pubkey(&public_key, network) != *from {
+        return Err("secret key does not match the sweep address".into());
+    }
+
+    let spendable: Vec<&ExplorerUtxo> = utxos
+        .iter()
+        .filter(|u| !coinselect::is_immature_coinbase(u, network))
+        .collect();
+    if spendable.is_empty() {
+        return Err("no spendable UTXOs to sweep".into());
+    }
+
+    let total: u64 = spendable.iter().map(|u| u.value_satoshis).sum();
+    let fee = estimate_p2pkh_size(spendable.len(), 1) as u64 * fee_rate;
+    if total <= fee {
+        return Err(format!("balance {total} sats does not cover fee {fee} sats").into());
+    }
+    if total - fee < DUST_LIMIT {
+        return Err(format!("{} sats left after the {fee} sat fee is below the dust limit", total - fee).into());
+    }
+
+    let mut builder = TransactionBuilder::new();
+    for u in &spendable {
+        builder.add_input(&u.txid, u.vout);
+    }
+    builder.add_output(to, total - fee)?;
+
+    // `from` is checked against the key above, so its script is what every input spends;
+    // the explorer's script_hex isn't trusted for signing
+    let prev_script = from.script_pubkey();
+    for i in 0..spendable.len() {
+        builder.sign_input(i, secret_key, &prev_script)?;
+    }
+
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::p2pkh_script_pubkey;
+
+    #[test]
+    fn test_build_sweep() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let script_hex = hex::encode(p2pkh_script_pubkey(from.pubkey_hash()).as_bytes());
+
+        let utxos: Vec<ExplorerUtxo> = [500_000_000u64, 250_000_000]
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| ExplorerUtxo {
+                txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+                vout: i as u32,
+                value_satoshis: v,
+                script_hex: script_hex.clone(),
+                confirmations: 5,
+                is_coinbase: false,
+            })
+            .collect();
+
+        let tx = build_sweep(&secret, &from, &utxos, &to, Network::Testnet, TESTNET_FEE_RATE).unwrap();
+        assert_eq!(tx.input.len(), 2);
+        assert!(tx.input.iter().all(|i| !i.script_sig.is_empty()));
+        assert_eq!(tx.output.len(), 1);
+        let fee = estimate_p2pkh_size(2, 1) as u64 * TESTNET_FEE_RATE;
+        assert_eq!(tx.output[0].value.to_sat(), 750_000_000 - fee);
+
+        assert!(build_sweep(&secret, &to, &utxos, &from, Network::Testnet, TESTNET_FEE_RATE).is_err());
+        assert!(sweep_faucet(&secret, &from, &to, Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_build_sweep_rejects_dust_and_ignores_script_hex() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let utxo = |value_satoshis| ExplorerUtxo {
+            txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+            vout: 0,
+            value_satoshis,
+            // Not the script `from` locks to: signing must not use it
+            script_hex: "6a".to_string(),
+            confirmations: 5,
+            is_coinbase: false,
+        };
+
+        let fee = estimate_p2pkh_size(1, 1) as u64 * TESTNET_FEE_RATE;
+        let err = build_sweep(&secret, &from, &[utxo(fee + DUST_LIMIT - 1)], &to, Network::Testnet, TESTNET_FEE_RATE);
+        assert!(err.unwrap_err().to_string().contains("dust"));
+
+        let tx = build_sweep(&secret, &from, &[utxo(fee + DUST_LIMIT)], &to, Network::Testnet, TESTNET_FEE_RATE).unwrap();
+        assert_eq!(tx.output[0].value.to_sat(), DUST_LIMIT);
+        assert!(crate::transaction::verify_input(&tx, 0, &from.script_pubkey()));
+    }
+}
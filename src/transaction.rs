@@ -1,14 +1,168 @@
-use bitcoin::{Transaction, TxIn, TxOut, OutPoint, Txid, Sequence, ScriptBuf};
-use bitcoin::opcodes::all::{OP_DUP, OP_HASH160, OP_EQUALVERIFY, OP_CHECKSIG, OP_EQUAL, OP_PUSHBYTES_0};
+use bitcoin::{Transaction, TxIn, TxOut, OutPoint, Txid, Sequence, ScriptBuf, Script};
+use bitcoin::opcodes::all::OP_PUSHBYTES_0;
 use bitcoin::blockdata::script::Builder as ScriptBuilder;
 use bitcoin::absolute::LockTime;
 use bitcoin::amount::Amount;
-use bitcoin::hashes::Hash;
+use bitcoin::hashes::{hash160, sha256d, Hash};
 use bitcoin::sighash::{SighashCache, EcdsaSighashType};
-use bitcoin::secp256k1::{Secp256k1, SecretKey, Message};
+use bitcoin::secp256k1::{Secp256k1, Message, PublicKey};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
 
 
-use crate::address::{AddressKind, DogeAddress};
+use crate::address::{AddressError, DogeAddress};
+use crate::coinselect::{self, ChangeDecision};
+use crate::explorer::ExplorerUtxo;
+use crate::network::{Network, MAX_MONEY_SATS};
+use crate::rpc::DogeRpcClient;
+use crate::signer::Signer;
+use crate::script::{self, ScriptType};
+
+#[derive(Debug)]
+pub enum TxError {
+    InvalidOutputOrder(String),
+    AlreadySigned(usize),
+    InvalidHex(String),
+    InputIndexOutOfRange(usize),
+    NonCanonicalSignature(usize),
+    NoInputs,
+    NoOutputs,
+    InsufficientFunds { available: u64, required: u64 },
+    InvalidSighashType { input: usize, byte: u8 },
+    OutputIndexOutOfRange(usize),
+    AmountOverflow,
+    /// A locktime is set but every input is final (`Sequence::MAX`), so consensus ignores it
+    LockTimeIneffective,
+    /// The original transaction doesn't signal BIP125 replace-by-fee
+    NotReplaceable,
+    /// The hex is a SegWit serialization, most likely a Bitcoin transaction
+    SegWitNotSupported,
+    /// OP_RETURN payload longer than `MAX_OP_RETURN_SIZE`
+    OpReturnTooLarge(usize),
+    PrevoutCountMismatch { inputs: usize, prevouts: usize },
+    /// A zero-value output that isn't OP_RETURN is non-standard
+    ZeroValueOutput,
+    InvalidAddress(AddressError),
+    /// No signing key was supplied for the outpoint spent by this input
+    MissingSigningKey(OutPoint),
+    /// The inputs other than the designated fee source already exceed the outputs
+    FeeSourceNotNeeded { surplus: u64 },
+    /// A replacement's total fee is below the BIP125 minimum
+    ReplacementFeeTooLow { fee: u64, required: u64 },
+    TooManyOutputs { count: usize, max: usize },
+}
+
+impl std::fmt::Display for TxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TxError::InvalidOutputOrder(e) => write!(f, "invalid output order: {e}"),
+            TxError::AlreadySigned(i) => write!(f, "input {i} is already signed"),
+            TxError::InvalidHex(e) => write!(f, "invalid transaction hex: {e}"),
+            TxError::InputIndexOutOfRange(i) => write!(f, "input index {i} out of range"),
+            TxError::NonCanonicalSignature(i) => write!(f, "non-canonical DER signature for input {i}"),
+            TxError::NoInputs => write!(f, "transaction has no inputs"),
+            TxError::NoOutputs => write!(f, "transaction has no outputs"),
+            TxError::InsufficientFunds { available, required } => {
+                write!(f, "insufficient funds: {available} sats available, {required} sats required")
+            }
+            TxError::InvalidSighashType { input, byte } => {
+                write!(f, "invalid sighash type 0x{byte:02x} on signature for input {input}")
+            }
+            TxError::OutputIndexOutOfRange(i) => write!(f, "output index {i} out of range"),
+            TxError::AmountOverflow => write!(f, "amount overflow"),
+            TxError::LockTimeIneffective => write!(f, "locktime is set but no input has a non-final sequence"),
+            TxError::NotReplaceable => write!(f, "transaction does not signal replace-by-fee and can't be bumped"),
+            TxError::OpReturnTooLarge(n) => write!(f, "OP_RETURN data is {n} bytes, at most {MAX_OP_RETURN_SIZE} are relayed"),
+            TxError::PrevoutCountMismatch { inputs, prevouts } => write!(f, "{inputs} inputs but {prevouts} prevouts"),
+            TxError::ZeroValueOutput => write!(f, "zero-value outputs are only standard for OP_RETURN (use add_op_return)"),
+            TxError::InvalidAddress(e) => write!(f, "invalid address: {e}"),
+            TxError::MissingSigningKey(outpoint) => write!(f, "no signing key for input spending {outpoint}"),
+            TxError::FeeSourceNotNeeded { surplus } => {
+                write!(f, "other inputs exceed the outputs by {surplus} sats; the fee source isn't needed")
+            }
+            TxError::TooManyOutputs { count, max } => write!(f, "{count} outputs exceeds the limit of {max}"),
+            TxError::ReplacementFeeTooLow { fee, required } => {
+                write!(f, "replacement pays {fee} sats in fees, BIP125 requires at least {required}")
+            }
+            TxError::SegWitNotSupported => {
+                write!(f, "transaction uses the SegWit serialization, which Dogecoin doesn't support (Bitcoin hex?)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TxError {}
+
+/// Non-fatal findings reported by `TransactionBuilder::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxWarning {
+    /// Two outputs pay the same scriptPubKey (bad for privacy)
+    OutputReuse(usize, usize),
+}
+
+impl std::fmt::Display for TxWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TxWarning::OutputReuse(a, b) => write!(f, "outputs {a} and {b} pay the same script"),
+        }
+    }
+}
+
+/// Typical size of a signed P2PKH input: outpoint (36) + scriptSig (~107) + sequence (4) + length byte
+pub const P2PKH_INPUT_SIZE: usize = 148;
+/// Size of a P2PKH output: value (8) + script length (1) + script (25)
+pub const P2PKH_OUTPUT_SIZE: usize = 34;
+/// Version (4) + input count (1) + output count (1) + locktime (4)
+pub const TX_OVERHEAD_SIZE: usize = 10;
+
+/// Outputs below this value are dust and won't be relayed (Dogecoin Core: 0.01 DOGE)
+pub const DUST_LIMIT: u64 = 1_000_000;
+
+/// Typical DER signature + sighash byte length (low-S signatures are 71-73 bytes)
+pub const AVG_SIG_SIZE: usize = 72;
+/// Largest possible DER signature + sighash byte length
+pub const MAX_SIG_SIZE: usize = 73;
+
+/// Proprietary PSBT key prefix under which `to_psbt` stores each input's prevout
+pub const PSBT_PREVOUT_PREFIX: &[u8] = b"doge-hack";
+
+/// Value of an anchor output from `add_anchor_output`: the smallest non-dust amount
+pub const ANCHOR_VALUE: u64 = DUST_LIMIT;
+
+/// Largest OP_RETURN payload Dogecoin Core relays as standard
+pub const MAX_OP_RETURN_SIZE: usize = 80;
+
+/// Default output cap: roughly what fits P2PKH outputs in a 100 kB standard transaction
+pub const DEFAULT_MAX_OUTPUTS: usize = 2_500;
+
+/// Estimate the serialized size of a transaction spending `n_inputs` P2PKH inputs to `n_outputs` P2PKH outputs
+pub fn estimate_p2pkh_size(n_inputs: usize, n_outputs: usize) -> usize {
+    TX_OVERHEAD_SIZE + n_inputs * P2PKH_INPUT_SIZE + n_outputs * P2PKH_OUTPUT_SIZE
+}
+
+/// How to sign one input in `TransactionBuilder::sign_mixed`
+pub enum InputSignSpec<'a> {
+    P2pkh {
+        input_index: usize,
+        signer: &'a dyn Signer,
+        prev_script: &'a ScriptBuf,
+    },
+    /// `signers` must follow the pubkey order of the redeem script
+    P2shMultisig {
+        input_index: usize,
+        signers: &'a [&'a dyn Signer],
+        redeem_script: &'a ScriptBuf,
+    },
+}
+
+impl InputSignSpec<'_> {
+    pub fn input_index(&self) -> usize {
+        match self {
+            InputSignSpec::P2pkh { input_index, .. } | InputSignSpec::P2shMultisig { input_index, .. } => *input_index,
+        }
+    }
+}
 
 /// Scaffolding for Dogecoin Transaction Construction
 /// 
@@ -17,20 +171,98 @@ use crate::address::{AddressKind, DogeAddress};
 
 #[derive(Clone)]
 pub struct TransactionBuilder {
+    version: bitcoin::transaction::Version,
+    lock_time: LockTime,
+    rbf: bool,
     inputs: Vec<TxIn>,
+    /// Spent script type per input, used for size estimation
+    input_types: Vec<ScriptType>,
     outputs: Vec<TxOut>,
+    max_outputs: usize,
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TransactionBuilder {
     pub fn new() -> Self {
         Self { 
+            version: bitcoin::transaction::Version::ONE, // Dogecoin uses Version 1 usually
+            lock_time: LockTime::ZERO,
+            rbf: true,
             inputs: Vec::new(),
+            input_types: Vec::new(),
             outputs: Vec::new(),
+            max_outputs: DEFAULT_MAX_OUTPUTS,
+        }
+    }
+
+    /// Load an existing transaction back into a builder (the inverse of `build`).
+    ///
+    /// Version, locktime, inputs (outpoint + sequence) and outputs are kept. ScriptSigs are
+    /// discarded because any edit invalidates them, so inputs must be signed again.
+    pub fn from_transaction(tx: &Transaction) -> Self {
+        let inputs: Vec<TxIn> = tx
+            .input
+            .iter()
+            .map(|i| TxIn {
+                previous_output: i.previous_output,
+                script_sig: ScriptBuf::new(),
+                sequence: i.sequence,
+                witness: bitcoin::Witness::default(),
+            })
+            .collect();
+
+        Self {
+            version: tx.version,
+            lock_time: tx.lock_time,
+            rbf: true,
+            input_types: vec![ScriptType::P2pkh; inputs.len()],
+            inputs,
+            outputs: tx.output.clone(),
+            max_outputs: DEFAULT_MAX_OUTPUTS,
         }
     }
 
+    /// Inputs added so far
+    pub fn inputs(&self) -> &[TxIn] {
+        &self.inputs
+    }
+
+    /// Outputs added so far
+    pub fn outputs(&self) -> &[TxOut] {
+        &self.outputs
+    }
+
     /// Add a UTXO as input (Hardcoded for now in early phases)
+    ///
+    /// The prevout is assumed to be P2PKH for size estimation; see `add_input_with_prevout`.
     pub fn add_input(&mut self, txid_hex: &str, vout: u32) {
+        self.push_input(txid_hex, vout, ScriptType::P2pkh);
+    }
+
+    /// Add an input, classifying its prevout scriptPubKey so fee estimates use the right input size.
+    ///
+    /// P2SH prevouts can't be sized from the scriptPubKey alone; call `set_input_script_type`
+    /// with `script::classify_redeem_script` once the redeem script is known.
+    pub fn add_input_with_prevout(&mut self, txid_hex: &str, vout: u32, prevout_script: &Script) {
+        self.push_input(txid_hex, vout, script::classify(prevout_script));
+    }
+
+    /// Override the spent script type used to estimate an input's size
+    pub fn set_input_script_type(&mut self, input_index: usize, script_type: ScriptType) -> Result<(), TxError> {
+        let slot = self
+            .input_types
+            .get_mut(input_index)
+            .ok_or(TxError::InputIndexOutOfRange(input_index))?;
+        *slot = script_type;
+        Ok(())
+    }
+
+    fn push_input(&mut self, txid_hex: &str, vout: u32, script_type: ScriptType) {
         let txid = Txid::from_str(txid_hex).expect("Invalid Hex Txid");
         let input = TxIn {
             previous_output: OutPoint { txid, vout },
@@ -39,54 +271,594 @@ impl TransactionBuilder {
             witness: bitcoin::Witness::default(),
         };
         self.inputs.push(input);
+        self.input_types.push(script_type);
     }
 
-    /// Add an output to a destination address
-    pub fn add_output(&mut self, address: &DogeAddress, amount_satoshis: u64) {
-        let hash160 = address.hash160();
-
-        let script_pubkey = match address.kind() {
-            AddressKind::P2pkh => ScriptBuilder::new()
-                .push_opcode(OP_DUP)
-                .push_opcode(OP_HASH160)
-                .push_slice(<&bitcoin::script::PushBytes>::try_from(hash160).expect("valid push bytes"))
-                .push_opcode(OP_EQUALVERIFY)
-                .push_opcode(OP_CHECKSIG)
-                .into_script(),
-            AddressKind::P2sh => ScriptBuilder::new()
-                .push_opcode(OP_HASH160)
-                .push_slice(<&bitcoin::script::PushBytes>::try_from(hash160).expect("valid push bytes"))
-                .push_opcode(OP_EQUAL)
-                .into_script(),
-        };
-
+    /// Add an output to a destination address.
+    ///
+    /// Zero amounts are rejected with `TxError::ZeroValueOutput`; `add_op_return` is the
+    /// way to add a zero-value output.
+    pub fn add_output(&mut self, address: &DogeAddress, amount_satoshis: u64) -> Result<(), TxError> {
+        if amount_satoshis == 0 {
+            return Err(TxError::ZeroValueOutput);
+        }
         let output = TxOut {
             value: Amount::from_sat(amount_satoshis),
-            script_pubkey: script_pubkey,
+            script_pubkey: address.script_pubkey(),
         };
         self.outputs.push(output);
+        Ok(())
+    }
+
+    /// Add an output to a base58 address string.
+    ///
+    /// P2SH addresses (`2...`/`A...`/`9...`) get `OP_HASH160 <hash> OP_EQUAL`, so a multisig
+    /// can be paid without knowing its redeem script.
+    pub fn add_output_str(&mut self, address: &str, amount_satoshis: u64) -> Result<(), TxError> {
+        let address = DogeAddress::from_base58(address).map_err(TxError::InvalidAddress)?;
+        self.add_output(&address, amount_satoshis)
+    }
+
+    /// Set the transaction's nLockTime
+    pub fn set_lock_time(&mut self, lock_time: LockTime) {
+        self.lock_time = lock_time;
+    }
+
+    /// Toggle replace-by-fee signaling (enabled by default).
+    ///
+    /// When disabled, every input's sequence is made final at build time: `0xFFFFFFFF`, or
+    /// `0xFFFFFFFE` if a locktime is set so it stays enforced. The same sequences are used
+    /// when computing sighashes, so it fails with `AlreadySigned` once any input is signed.
+    pub fn set_rbf(&mut self, enabled: bool) -> Result<(), TxError> {
+        if let Some(i) = self.inputs.iter().position(|i| !i.script_sig.is_empty()) {
+            return Err(TxError::AlreadySigned(i));
+        }
+        self.rbf = enabled;
+        Ok(())
+    }
+
+    /// Cap the number of outputs `validate` accepts (`DEFAULT_MAX_OUTPUTS` by default).
+    ///
+    /// Guards batch payouts against building a transaction too large to relay.
+    pub fn set_max_outputs(&mut self, max_outputs: usize) {
+        self.max_outputs = max_outputs;
+    }
+
+    /// Total value of the outputs added so far
+    pub fn output_total(&self) -> u64 {
+        self.outputs.iter().map(|o| o.value.to_sat()).sum()
+    }
+
+    /// Add a change output paying an exact absolute fee.
+    ///
+    /// Change is `total_input_sats - outputs - absolute_fee_sats`. If that is below
+    /// `DUST_LIMIT` no change output is added and the remainder goes to the miner.
+    /// Returns the change amount actually added.
+    pub fn add_change_with_fee(
+        &mut self,
+        change_address: &DogeAddress,
+        total_input_sats: u64,
+        absolute_fee_sats: u64,
+    ) -> Result<Option<u64>, TxError> {
+        let outputs = self.output_total();
+        match coinselect::will_produce_change(total_input_sats, outputs, absolute_fee_sats, DUST_LIMIT) {
+            ChangeDecision::WithChange(change) => {
+                self.add_output(change_address, change)?;
+                Ok(Some(change))
+            }
+            ChangeDecision::NoChangeAbsorbedToFee(_) => Ok(None),
+            ChangeDecision::InsufficientFunds => Err(TxError::InsufficientFunds {
+                available: total_input_sats,
+                required: outputs.saturating_add(absolute_fee_sats),
+            }),
+        }
+    }
+
+    /// Add `change_sats` to an existing output instead of creating a change output.
+    ///
+    /// Saves an output (and a future UTXO) when the change is small and the recipient
+    /// agrees to receive it.
+    pub fn fold_change_into_output(&mut self, output_index: usize, change_sats: u64) -> Result<(), TxError> {
+        let output = self
+            .outputs
+            .get_mut(output_index)
+            .ok_or(TxError::OutputIndexOutOfRange(output_index))?;
+        let value = output.value.to_sat().checked_add(change_sats).ok_or(TxError::AmountOverflow)?;
+        output.value = Amount::from_sat(value);
+        Ok(())
+    }
+
+    /// Add change as two outputs of random proportions to obscure the payment amount.
+    ///
+    /// The split is reproducible for a given `seed`. Both parts stay at or above
+    /// `DUST_LIMIT`; if that's impossible the change goes to a single output.
+    pub fn add_split_change(&mut self, change_address: &DogeAddress, total_change_sats: u64, seed: u64) -> Result<(), TxError> {
+        if total_change_sats < 2 * DUST_LIMIT {
+            return self.add_output(change_address, total_change_sats);
+        }
+        let first = StdRng::seed_from_u64(seed).gen_range(DUST_LIMIT..=total_change_sats - DUST_LIMIT);
+        self.add_output(change_address, first)?;
+        self.add_output(change_address, total_change_sats - first)
+    }
+
+    /// Start an unsigned replacement of `original` that pays `additional_fee` more,
+    /// taken from the change output at `change_index`.
+    ///
+    /// Fails with `TxError::NotReplaceable` if `original` doesn't signal RBF, since the node
+    /// would reject the replacement. The new fee must reach `min_replacement_fee` for the
+    /// replacement's worst-case size at `incremental_relay_fee` sat/byte, and the change must
+    /// stay above `DUST_LIMIT`. Re-sign every input before broadcasting.
+    pub fn bump_fee(
+        original: &Transaction,
+        change_index: usize,
+        additional_fee: u64,
+        original_fee: u64,
+        incremental_relay_fee: u64,
+    ) -> Result<Self, TxError> {
+        if !is_rbf_signaled(original) {
+            return Err(TxError::NotReplaceable);
+        }
+        let mut builder = Self::from_transaction(original);
+        let fee = original_fee.saturating_add(additional_fee);
+        let required = min_replacement_fee(original_fee, builder.worst_case_vsize(), incremental_relay_fee);
+        if fee < required {
+            return Err(TxError::ReplacementFeeTooLow { fee, required });
+        }
+        let change = builder
+            .outputs
+            .get_mut(change_index)
+            .ok_or(TxError::OutputIndexOutOfRange(change_index))?;
+        let available = change.value.to_sat();
+        let required = additional_fee.saturating_add(DUST_LIMIT);
+        if available < required {
+            return Err(TxError::InsufficientFunds { available, required });
+        }
+        change.value = Amount::from_sat(available - additional_fee);
+        Ok(builder)
+    }
+
+    /// Reorder the current outputs to match an agreed template.
+    ///
+    /// `indices[i]` is the index of the existing output that should end up at position `i`,
+    /// so `indices` must be a permutation of `0..outputs.len()`.
+    /// Must be called before signing, since reordering outputs invalidates SIGHASH_ALL signatures.
+    pub fn with_output_order(&mut self, indices: &[usize]) -> Result<(), TxError> {
+        if let Some(i) = self.inputs.iter().position(|i| !i.script_sig.is_empty()) {
+            return Err(TxError::AlreadySigned(i));
+        }
+
+        let n = self.outputs.len();
+        if indices.len() != n {
+            return Err(TxError::InvalidOutputOrder(format!("expected {n} indices, got {}", indices.len())));
+        }
+
+        let mut seen = vec![false; n];
+        for &i in indices {
+            if i >= n {
+                return Err(TxError::InvalidOutputOrder(format!("index {i} out of range")));
+            }
+            if seen[i] {
+                return Err(TxError::InvalidOutputOrder(format!("duplicate index {i}")));
+            }
+            seen[i] = true;
+        }
+
+        self.outputs = indices.iter().map(|&i| self.outputs[i].clone()).collect();
+        Ok(())
+    }
+
+    /// Add a zero-value OP_RETURN output carrying `data` (at most `MAX_OP_RETURN_SIZE` bytes)
+    pub fn add_op_return(&mut self, data: &[u8]) -> Result<(), TxError> {
+        if data.len() > MAX_OP_RETURN_SIZE {
+            return Err(TxError::OpReturnTooLarge(data.len()));
+        }
+        let push: &bitcoin::script::PushBytes = data.try_into().expect("80 bytes fit in a single push");
+        self.outputs.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::new_op_return(push),
+        });
+        Ok(())
+    }
+
+    /// Add an `ANCHOR_VALUE` P2PKH output to `pubkey`, returning its index.
+    ///
+    /// The anchor lets the key holder CPFP the transaction later even when its fee was fixed
+    /// at signing time (pre-signed or channel-style transactions). The cost is an extra
+    /// output now, plus a near-dust UTXO to sweep if it's never used; Dogecoin has no
+    /// zero-value anchors, so it always locks up `ANCHOR_VALUE`.
+    pub fn add_anchor_output(&mut self, pubkey: &PublicKey) -> usize {
+        let pubkey_hash = hash160::Hash::hash(&pubkey.serialize());
+        self.outputs.push(TxOut {
+            value: Amount::from_sat(ANCHOR_VALUE),
+            script_pubkey: script::p2pkh_script_pubkey(pubkey_hash.as_byte_array()),
+        });
+        self.outputs.len() - 1
+    }
+
+    /// Add several outputs at once, in order. Nothing is added if any amount is zero.
+    pub fn add_outputs(&mut self, outputs: &[(DogeAddress, u64)]) -> Result<(), TxError> {
+        if outputs.iter().any(|(_, amount)| *amount == 0) {
+            return Err(TxError::ZeroValueOutput);
+        }
+        for (address, amount) in outputs {
+            self.add_output(address, *amount)?;
+        }
+        Ok(())
+    }
+
+    /// Estimated size in bytes once signed, assuming a typical 72-byte signature per unsigned P2PKH input.
+    ///
+    /// Dogecoin has no SegWit, so there is no witness discount: vsize equals the serialized size
+    /// and weight is always 4 units per byte, for this and every other size helper here. Fee code
+    /// ported from Bitcoin that discounts witness bytes will under-pay. Already-signed inputs are
+    /// counted as-is.
+    pub fn estimate_vsize(&self) -> usize {
+        self.estimate_size_with_sig(AVG_SIG_SIZE)
+    }
+
+    /// Virtual size in bytes, for showing fee rates in sat/vByte; same as `estimate_vsize`.
+    pub fn vbytes(&self) -> usize {
+        self.estimate_vsize()
+    }
+
+    /// Weight units as BIP141 defines them: always `vbytes() * 4`.
+    pub fn weight(&self) -> usize {
+        self.vbytes() * 4
+    }
+
+    /// Conservative size estimate assuming the maximum 73-byte signature per unsigned input.
+    ///
+    /// Use this for "must confirm" spends: with many inputs the 71-73 byte signature variance adds up.
+    pub fn worst_case_vsize(&self) -> usize {
+        self.estimate_size_with_sig(MAX_SIG_SIZE)
+    }
+
+    fn estimate_size_with_sig(&self, sig_size: usize) -> usize {
+        // An input with an empty scriptSig: outpoint + 1-byte length + sequence
+        const EMPTY_INPUT_SIZE: usize = 41;
+
+        let unsigned_extra: usize = self
+            .inputs
+            .iter()
+            .zip(&self.input_types)
+            .filter(|(input, _)| input.script_sig.is_empty())
+            .map(|(_, script_type)| script::input_size_with_sig(script_type, sig_size) - EMPTY_INPUT_SIZE)
+            .sum();
+        self.to_transaction_ref().total_size() + unsigned_extra
+    }
+
+    /// Pairs of output indices `(i, j)` with `i < j` that pay an identical scriptPubKey
+    pub fn detect_output_reuse(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..self.outputs.len() {
+            for j in (i + 1)..self.outputs.len() {
+                if self.outputs[i].script_pubkey == self.outputs[j].script_pubkey {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Check the transaction before building.
+    ///
+    /// Structural problems are returned as errors; privacy issues such as
+    /// output reuse are returned as warnings.
+    pub fn validate(&self) -> Result<Vec<TxWarning>, TxError> {
+        if self.inputs.is_empty() {
+            return Err(TxError::NoInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(TxError::NoOutputs);
+        }
+        if self.outputs.len() > self.max_outputs {
+            return Err(TxError::TooManyOutputs { count: self.outputs.len(), max: self.max_outputs });
+        }
+        if self.lock_time_ineffective() {
+            return Err(TxError::LockTimeIneffective);
+        }
+        self.outputs
+            .iter()
+            .try_fold(0u64, |total, o| total.checked_add(o.value.to_sat()).filter(|t| *t <= MAX_MONEY_SATS))
+            .ok_or(TxError::AmountOverflow)?;
+
+        Ok(self
+            .detect_output_reuse()
+            .into_iter()
+            .map(|(a, b)| TxWarning::OutputReuse(a, b))
+            .collect())
+    }
+
+    /// Change amount that makes the transaction pay at least `fee_rate` sat/byte once a change
+    /// output with a `change_script_size`-byte scriptPubKey is added.
+    ///
+    /// Adding the change output grows the transaction, which raises the fee and shrinks the
+    /// change, so this iterates size -> fee -> change until the value stops moving. Returns
+    /// `None` if the inputs can't cover the fee or the change would be dust.
+    pub fn solve_change(&self, inputs_total: u64, fee_rate: u64, change_script_size: usize) -> Option<u64> {
+        let outputs_total = self.output_total();
+        let mut probe = self.clone();
+        probe.outputs.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::from_bytes(vec![0u8; change_script_size]),
+        });
+
+        let mut change = 0u64;
+        loop {
+            probe.outputs.last_mut().expect("change output").value = Amount::from_sat(change);
+            let fee = probe.estimate_vsize() as u64 * fee_rate;
+            let next = inputs_total.checked_sub(outputs_total)?.checked_sub(fee)?;
+            if next == change {
+                break;
+            }
+            change = next;
+        }
+
+        (change >= DUST_LIMIT).then_some(change)
+    }
+
+    /// Add change back to `change_address` at `fee_rate` sat/byte, then build.
+    ///
+    /// Change below `DUST_LIMIT` is dropped and goes to the miner.
+    pub fn build_with_change(
+        self,
+        change_address: &DogeAddress,
+        total_input_sats: u64,
+        fee_rate: u64,
+    ) -> Result<Transaction, TxError> {
+        self.build_with_change_split(std::slice::from_ref(change_address), total_input_sats, fee_rate)
+    }
+
+    /// Like `build_with_change`, but splits the change equally across `change_addresses`.
+    ///
+    /// If the per-address share would be dust, trailing addresses are dropped until every
+    /// change output clears `DUST_LIMIT` (or no change is left). Inputs always equal outputs
+    /// plus fee exactly.
+    pub fn build_with_change_split(
+        mut self,
+        change_addresses: &[DogeAddress],
+        total_input_sats: u64,
+        fee_rate: u64,
+    ) -> Result<Transaction, TxError> {
+        let base_size = self.estimate_vsize();
+        let outputs = self.output_total();
+
+        for count in (0..=change_addresses.len()).rev() {
+            let addresses = &change_addresses[..count];
+            let change_size: usize = addresses.iter().map(|a| 9 + a.script_pubkey().len()).sum();
+            let fee = (base_size + change_size) as u64 * fee_rate;
+            let required = outputs.saturating_add(fee);
+            if total_input_sats < required {
+                if count == 0 {
+                    return Err(TxError::InsufficientFunds { available: total_input_sats, required });
+                }
+                continue;
+            }
+            if count == 0 {
+                break;
+            }
+
+            let change = total_input_sats - required;
+            let share = change / count as u64;
+            if share < DUST_LIMIT {
+                continue;
+            }
+            // The first output takes the rounding remainder so the total is exact
+            let remainder = change - share * count as u64;
+            for (i, address) in addresses.iter().enumerate() {
+                self.add_output(address, if i == 0 { share + remainder } else { share })?;
+            }
+            break;
+        }
+
+        Ok(self.build())
+    }
+
+    /// Build with input `fee_source_input` paying the fee, so all change comes from that UTXO.
+    ///
+    /// `input_values[i]` is the value of input `i`. The other inputs go entirely to the
+    /// outputs; the fee source covers the rest of the outputs plus `fee_sats`, and its
+    /// remainder returns to `change_address` (dropped as fee if below `DUST_LIMIT`).
+    /// Fails with `FeeSourceNotNeeded` if the other inputs alone overfund the outputs, since
+    /// their surplus would then leak into the change.
+    pub fn build_with_fee_source(
+        mut self,
+        fee_source_input: usize,
+        input_values: &[u64],
+        fee_sats: u64,
+        change_address: &DogeAddress,
+    ) -> Result<Transaction, TxError> {
+        if input_values.len() != self.inputs.len() {
+            return Err(TxError::PrevoutCountMismatch { inputs: self.inputs.len(), prevouts: input_values.len() });
+        }
+        let source_value = *input_values.get(fee_source_input).ok_or(TxError::InputIndexOutOfRange(fee_source_input))?;
+        let others = input_values
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != fee_source_input)
+            .try_fold(0u64, |total, (_, v)| total.checked_add(*v))
+            .ok_or(TxError::AmountOverflow)?;
+
+        let outputs = self.output_total();
+        let share = outputs.checked_sub(others).ok_or_else(|| TxError::FeeSourceNotNeeded { surplus: others - outputs })?;
+        let required = share.saturating_add(fee_sats);
+        let change = source_value
+            .checked_sub(required)
+            .ok_or(TxError::InsufficientFunds { available: source_value, required })?;
+        if change >= DUST_LIMIT {
+            self.add_output(change_address, change)?;
+        }
+        Ok(self.build())
+    }
+
+    /// Build and sign a payment so the same arguments always give byte-identical hex.
+    ///
+    /// Signatures are deterministic (RFC6979). With `bip69` set, inputs are sorted by txid
+    /// then vout and outputs by amount then scriptPubKey (BIP69); otherwise inputs follow
+    /// `utxos` and outputs follow `recipients` with change last. Change comes from
+    /// `solve_change` and is dropped if it would be dust. All inputs are signed by `signer`.
+    pub fn build_deterministic(
+        utxos: &[ExplorerUtxo],
+        recipients: &[(DogeAddress, u64)],
+        change_address: &DogeAddress,
+        fee_rate: u64,
+        bip69: bool,
+        signer: &dyn Signer,
+    ) -> Result<Transaction, TxError> {
+        let mut utxos = utxos.to_vec();
+        if bip69 {
+            utxos.sort_by(|a, b| a.txid.cmp(&b.txid).then(a.vout.cmp(&b.vout)));
+        }
+
+        let mut builder = TransactionBuilder::new();
+        let mut prev_scripts = Vec::with_capacity(utxos.len());
+        for u in &utxos {
+            let prev_script = ScriptBuf::from_bytes(hex::decode(&u.script_hex).map_err(|e| TxError::InvalidHex(e.to_string()))?);
+            builder.add_input_with_prevout(&u.txid, u.vout, &prev_script);
+            prev_scripts.push(prev_script);
+        }
+        builder.add_outputs(recipients)?;
+
+        let inputs_total: u64 = utxos.iter().map(|u| u.value_satoshis).sum();
+        let change_script = change_address.script_pubkey();
+        match builder.solve_change(inputs_total, fee_rate, change_script.len()) {
+            Some(change) => builder.add_output(change_address, change)?,
+            None => {
+                let required = builder.output_total() + builder.estimate_vsize() as u64 * fee_rate;
+                if inputs_total < required {
+                    return Err(TxError::InsufficientFunds { available: inputs_total, required });
+                }
+            }
+        }
+
+        if bip69 {
+            builder
+                .outputs
+                .sort_by(|a, b| a.value.cmp(&b.value).then_with(|| a.script_pubkey.as_bytes().cmp(b.script_pubkey.as_bytes())));
+        }
+        for (i, prev_script) in prev_scripts.iter().enumerate() {
+            builder.sign_input(i, signer, prev_script)?;
+        }
+        Ok(builder.build())
+    }
+
+    /// Export the unsigned transaction as a BIP174 PSBT, e.g. for `walletprocesspsbt`.
+    ///
+    /// `prevouts` holds the scriptPubKey and value of each input, in input order. BIP174 only
+    /// carries full previous transactions for legacy inputs, which a signing node looks up in
+    /// its own wallet, so the prevouts are stored as proprietary input fields
+    /// (`PSBT_PREVOUT_PREFIX`, subtype 0, consensus-encoded `TxOut`).
+    pub fn to_psbt(&self, prevouts: &[(ScriptBuf, u64)]) -> Result<Vec<u8>, TxError> {
+        if let Some(i) = self.inputs.iter().position(|i| !i.script_sig.is_empty()) {
+            return Err(TxError::AlreadySigned(i));
+        }
+        if prevouts.len() != self.inputs.len() {
+            return Err(TxError::PrevoutCountMismatch { inputs: self.inputs.len(), prevouts: prevouts.len() });
+        }
+
+        let mut psbt = bitcoin::Psbt::from_unsigned_tx(self.to_transaction_ref()).expect("inputs are unsigned");
+        for (input, (script_pubkey, value)) in psbt.inputs.iter_mut().zip(prevouts) {
+            let key = bitcoin::psbt::raw::ProprietaryKey { prefix: PSBT_PREVOUT_PREFIX.to_vec(), subtype: 0, key: Vec::new() };
+            let prevout = TxOut { value: Amount::from_sat(*value), script_pubkey: script_pubkey.clone() };
+            input.proprietary.insert(key, bitcoin::consensus::encode::serialize(&prevout));
+        }
+        Ok(psbt.serialize())
+    }
+
+    /// Check that Dogecoin Core decodes the current transaction to the same txid and size.
+    ///
+    /// Sends the serialized hex to `decoderawtransaction`; any mismatch means our
+    /// serialization has drifted from the node's. Meant for tests against a regtest node.
+    pub fn verify_against_node(&self, rpc: &DogeRpcClient) -> Result<(), Box<dyn std::error::Error>> {
+        let tx = self.to_transaction_ref();
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+        let decoded = rpc.decode_raw_transaction(&tx_hex)?;
+
+        let local_txid = tx.compute_txid().to_string();
+        let node_txid = decoded.get("txid").and_then(|v| v.as_str()).ok_or("No txid in decoderawtransaction result")?;
+        if node_txid != local_txid {
+            return Err(format!("txid mismatch: local {local_txid}, node {node_txid}").into());
+        }
+        if let Some(node_hex) = decoded.get("hex").and_then(|v| v.as_str()) {
+            if node_hex != tx_hex {
+                return Err("hex mismatch between local and node serialization".into());
+            }
+        }
+        if let Some(node_size) = decoded.get("size").and_then(|v| v.as_u64()) {
+            if node_size != tx.total_size() as u64 {
+                return Err(format!("size mismatch: local {}, node {node_size}", tx.total_size()).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// True when a non-zero locktime is set but every input's sequence is final,
+    /// so the timelock would silently not apply
+    fn lock_time_ineffective(&self) -> bool {
+        self.lock_time != LockTime::ZERO && self.final_inputs().iter().all(|i| i.sequence == Sequence::MAX)
     }
 
     /// Build the final transaction
+    ///
+    /// Logs a warning if the locktime would be ignored; `validate` reports it as
+    /// `TxError::LockTimeIneffective`.
     pub fn build(self) -> Transaction {
+        if self.lock_time_ineffective() {
+            log::warn!("locktime {} is ignored: every input has a final sequence", self.lock_time);
+        }
+        let input = self.final_inputs();
         Transaction {
-            version: bitcoin::transaction::Version::ONE, // Dogecoin uses Version 1 usually
-            lock_time: LockTime::ZERO,
-            input: self.inputs,
+            version: self.version,
+            lock_time: self.lock_time,
+            input,
             output: self.outputs,
         }
     }
 
+    /// Consensus-serialized bytes of the transaction as `build` would produce it
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bitcoin::consensus::encode::serialize(&self.to_transaction_ref())
+    }
+
     /// Sign a specific input (Classic P2PKH)
     /// WARNING: This modifies the `inputs` in place.
+    ///
+    /// Inputs can be signed in any order: a fresh `SighashCache` is built per call, and the
+    /// legacy SIGHASH_ALL algorithm blanks every other input's scriptSig before hashing, so
+    /// signatures already placed on other inputs never leak into this input's digest.
+    ///
+    /// Any `Signer` works, so a hardware wallet can sign without exposing its key;
+    /// a plain `SecretKey` is a `Signer` too.
+    ///
+    /// Fails with `TxError::AlreadySigned` if the input already has a scriptSig; use
+    /// `force_sign_input` to re-sign on purpose.
     pub fn sign_input(
+        &mut self,
+        input_index: usize,
+        signer: &dyn Signer,
+        previous_script_pubkey: &ScriptBuf,
+    ) -> Result<(), TxError> {
+        let input = self.inputs.get(input_index).ok_or(TxError::InputIndexOutOfRange(input_index))?;
+        if !input.script_sig.is_empty() {
+            return Err(TxError::AlreadySigned(input_index));
+        }
+        self.force_sign_input(input_index, signer, previous_script_pubkey);
+        Ok(())
+    }
+
+    /// Like `sign_input`, but replaces any existing scriptSig.
+    ///
+    /// Panics if `input_index` is out of range.
+    pub fn force_sign_input(
         &mut self, 
         input_index: usize, 
-        secret_key: &SecretKey, 
+        signer: &dyn Signer, 
         previous_script_pubkey: &ScriptBuf
     ) {
-        let secp = Secp256k1::new();
-        let public_key = bitcoin::secp256k1::PublicKey::from_secret_key(&secp, secret_key);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("sign_input", input = input_index).entered();
+
+        let public_key = signer.public_key();
 
         // 1. Create the transaction to sign
         // We need a temporary transaction structure because SighashCache borrows it
@@ -101,10 +873,14 @@ impl TransactionBuilder {
                 EcdsaSighashType::All.to_u32()
             )
             .expect("Sighash generation failed");
+        #[cfg(feature = "tracing")]
+        tracing::debug!(sighash = %hex::encode(sighash.to_byte_array()), "computed sighash");
 
         // 3. Sign the Hash
         let message = Message::from_digest(sighash.to_byte_array());
-        let signature = secp.sign_ecdsa(&message, secret_key);
+        let signature = signer.sign_ecdsa(&message);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(der = %hex::encode(signature.serialize_der()), "signed");
         
         // 4. Construct ScriptSig: <Sig> <PubKey>
         let mut sig_with_hashtype = signature.serialize_der().to_vec();
@@ -114,26 +890,53 @@ impl TransactionBuilder {
             .push_slice(<&bitcoin::script::PushBytes>::try_from(sig_with_hashtype.as_slice()).unwrap())
             .push_slice(<&bitcoin::script::PushBytes>::try_from(public_key.serialize().as_slice()).unwrap())
             .into_script();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(script_sig_len = script_sig.len(), "built scriptSig");
 
         // 5. Update Input
         self.inputs[input_index].script_sig = script_sig;
     }
 
+    /// Sign an input spending a P2SH-wrapped P2PKH output (`sh(pkh(...))`).
+    ///
+    /// The redeem script is derived from the signer's public key and used as the scriptCode.
+    /// The resulting scriptSig is: <sig> <pubkey> <redeem_script>
+    pub fn sign_sh_pkh_input(&mut self, input_index: usize, signer: &dyn Signer) -> Result<(), TxError> {
+        let pubkey_hash = hash160::Hash::hash(&signer.public_key().serialize());
+        let redeem_script = script::sh_pkh_redeem_script(pubkey_hash.as_byte_array());
+        self.sign_input(input_index, signer, &redeem_script)?;
+
+        let mut script_sig = self.inputs[input_index].script_sig.to_bytes();
+        script_sig.extend_from_slice(
+            ScriptBuilder::new()
+                .push_slice(<&bitcoin::script::PushBytes>::try_from(redeem_script.as_bytes()).expect("valid push bytes"))
+                .as_bytes(),
+        );
+        self.inputs[input_index].script_sig = ScriptBuf::from_bytes(script_sig);
+        Ok(())
+    }
+
     /// Sign a legacy P2SH multisig input.
     ///
-    /// `redeem_script` is used as the scriptCode for legacy sighash.
+    /// `redeem_script` is used as the scriptCode for legacy sighash, and each of `signers`
+    /// (local keys or external devices) signs it in the redeem script's pubkey order.
     /// The resulting scriptSig is: OP_0 <sig1> <sig2> ... <redeem_script>
+    ///
+    /// Like `sign_input`, fails without changing anything if the input is already signed.
     pub fn sign_input_p2sh_multisig(
         &mut self,
         input_index: usize,
-        secret_keys: &[SecretKey],
+        signers: &[&dyn Signer],
         redeem_script: &ScriptBuf,
-    ) {
-        let secp = Secp256k1::new();
+    ) -> Result<(), TxError> {
+        let input = self.inputs.get(input_index).ok_or(TxError::InputIndexOutOfRange(input_index))?;
+        if !input.script_sig.is_empty() {
+            return Err(TxError::AlreadySigned(input_index));
+        }
         let tx = self.to_transaction_ref();
 
-        let mut sigs: Vec<Vec<u8>> = Vec::with_capacity(secret_keys.len());
-        for sk in secret_keys {
+        let mut sigs: Vec<Vec<u8>> = Vec::with_capacity(signers.len());
+        for signer in signers {
             let sighash_cache = SighashCache::new(&tx);
             let sighash = sighash_cache
                 .legacy_signature_hash(
@@ -144,7 +947,7 @@ impl TransactionBuilder {
                 .expect("Sighash generation failed");
 
             let message = Message::from_digest(sighash.to_byte_array());
-            let signature = secp.sign_ecdsa(&message, sk);
+            let signature = signer.sign_ecdsa(&message);
 
             let mut sig_with_hashtype = signature.serialize_der().to_vec();
             sig_with_hashtype.push(EcdsaSighashType::All.to_u32() as u8);
@@ -158,31 +961,598 @@ impl TransactionBuilder {
 
         b = b.push_slice(<&bitcoin::script::PushBytes>::try_from(redeem_script.as_bytes()).expect("valid push bytes"));
         self.inputs[input_index].script_sig = b.into_script();
+        Ok(())
+    }
+
+    /// Sign inputs of different types in one pass, e.g. a P2PKH input next to a P2SH multisig one.
+    ///
+    /// Each input's legacy sighash only commits to its own scriptCode, so the order of the
+    /// specs doesn't matter and one input's signature can't corrupt another's. Every spec is
+    /// checked first, so nothing is signed if any index is out of range, already signed, or
+    /// named twice.
+    pub fn sign_mixed(&mut self, specs: &[InputSignSpec]) -> Result<(), TxError> {
+        let mut seen = vec![false; self.inputs.len()];
+        for index in specs.iter().map(InputSignSpec::input_index) {
+            let input = self.inputs.get(index).ok_or(TxError::InputIndexOutOfRange(index))?;
+            if !input.script_sig.is_empty() || std::mem::replace(&mut seen[index], true) {
+                return Err(TxError::AlreadySigned(index));
+            }
+        }
+
+        for spec in specs {
+            match *spec {
+                InputSignSpec::P2pkh { input_index, signer, prev_script } => {
+                    self.sign_input(input_index, signer, prev_script)?
+                }
+                InputSignSpec::P2shMultisig { input_index, signers, redeem_script } => {
+                    self.sign_input_p2sh_multisig(input_index, signers, redeem_script)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sign every P2PKH input with the signer and prevout script stored under its outpoint.
+    ///
+    /// Keyed by outpoint rather than position, so it stays correct after inputs are reordered
+    /// (e.g. by BIP69 sorting). Nothing is signed unless every input has an entry and none is
+    /// signed yet.
+    pub fn sign_all_inputs(&mut self, keys: &HashMap<OutPoint, (&dyn Signer, ScriptBuf)>) -> Result<(), TxError> {
+        for (i, input) in self.inputs.iter().enumerate() {
+            if !keys.contains_key(&input.previous_output) {
+                return Err(TxError::MissingSigningKey(input.previous_output));
+            }
+            if !input.script_sig.is_empty() {
+                return Err(TxError::AlreadySigned(i));
+            }
+        }
+
+        for i in 0..self.inputs.len() {
+            let (signer, prev_script) = &keys[&self.inputs[i].previous_output];
+            self.sign_input(i, *signer, prev_script)?;
+        }
+        Ok(())
+    }
+
+    /// Legacy SIGHASH_ALL digest for an input, for signing outside the builder (e.g. on a device).
+    ///
+    /// `script_code` is the prevout scriptPubKey for P2PKH, or the redeem script for P2SH.
+    pub fn legacy_sighash(&self, input_index: usize, script_code: &ScriptBuf) -> Result<[u8; 32], TxError> {
+        if input_index >= self.inputs.len() {
+            return Err(TxError::InputIndexOutOfRange(input_index));
+        }
+
+        let tx = self.to_transaction_ref();
+        let sighash = SighashCache::new(&tx)
+            .legacy_signature_hash(input_index, script_code, EcdsaSighashType::All.to_u32())
+            .expect("Sighash generation failed");
+        Ok(sighash.to_byte_array())
+    }
+
+    /// Attach an externally produced P2PKH signature to an input.
+    ///
+    /// `signature` is a DER signature with or without the trailing sighash-type byte; the
+    /// DER length header tells the two apart. A bare signature gets SIGHASH_ALL appended,
+    /// and a trailing byte that isn't a defined sighash type is rejected rather than signed
+    /// over twice. The result must be canonical (see `is_canonical_der`), so malformed
+    /// signatures are caught here instead of by a node.
+    pub fn apply_signature(
+        &mut self,
+        input_index: usize,
+        signature: &[u8],
+        public_key: &bitcoin::secp256k1::PublicKey,
+    ) -> Result<(), TxError> {
+        if input_index >= self.inputs.len() {
+            return Err(TxError::InputIndexOutOfRange(input_index));
+        }
+
+        let der_len = match signature {
+            [0x30, len, ..] => *len as usize + 2,
+            _ => return Err(TxError::NonCanonicalSignature(input_index)),
+        };
+        let mut sig_with_sighash = signature.to_vec();
+        if signature.len() == der_len {
+            sig_with_sighash.push(EcdsaSighashType::All.to_u32() as u8);
+        } else if signature.len() == der_len + 1 {
+            let byte = signature[der_len];
+            if EcdsaSighashType::from_standard(byte as u32).is_err() {
+                return Err(TxError::InvalidSighashType { input: input_index, byte });
+            }
+        } else {
+            return Err(TxError::NonCanonicalSignature(input_index));
+        }
+        let sig_with_sighash = sig_with_sighash.as_slice();
+
+        if !is_canonical_der(sig_with_sighash) {
+            return Err(TxError::NonCanonicalSignature(input_index));
+        }
+
+        let script_sig = ScriptBuilder::new()
+            .push_slice(<&bitcoin::script::PushBytes>::try_from(sig_with_sighash).expect("valid push bytes"))
+            .push_slice(<&bitcoin::script::PushBytes>::try_from(public_key.serialize().as_slice()).expect("valid push bytes"))
+            .into_script();
+        self.inputs[input_index].script_sig = script_sig;
+        Ok(())
     }
 
     // Helper to create a transaction reference for SighashCache
     fn to_transaction_ref(&self) -> Transaction {
         Transaction {
-            version: bitcoin::transaction::Version::ONE,
-            lock_time: LockTime::ZERO,
-            input: self.inputs.clone(),
+            version: self.version,
+            lock_time: self.lock_time,
+            input: self.final_inputs(),
             output: self.outputs.clone(),
         }
     }
+
+    // Inputs with sequences adjusted for the RBF toggle
+    fn final_inputs(&self) -> Vec<TxIn> {
+        let mut inputs = self.inputs.clone();
+        if !self.rbf {
+            let sequence = if self.lock_time == LockTime::ZERO {
+                Sequence::MAX
+            } else {
+                Sequence::ENABLE_LOCKTIME_NO_RBF
+            };
+            for input in &mut inputs {
+                input.sequence = sequence;
+            }
+        }
+        inputs
+    }
 }
 
 
+/// Split a payment to one destination into `parts` outputs.
+///
+/// With `jitter` (a fraction in `[0, 1)`, e.g. `0.2` for +/-20%), each chunk is randomly
+/// weighted so the amounts are not obviously equal; the weights are reproducible for a
+/// given `seed`, as in `add_split_change`. Every chunk is at least `DUST_LIMIT`, so
+/// the result can go straight to `add_outputs`; only the amount above that floor is weighted.
+/// The chunks always sum to `total` exactly; the rounding remainder goes to the last chunk.
+///
+/// Fails with `InsufficientFunds` when `total` can't give every part `DUST_LIMIT`.
+pub fn split_output(
+    address: &DogeAddress,
+    total: u64,
+    parts: usize,
+    jitter: Option<f64>,
+    seed: u64,
+) -> Result<Vec<(DogeAddress, u64)>, TxError> {
+    let parts = parts.max(1);
+    let jitter = jitter.unwrap_or(0.0).clamp(0.0, 0.99);
+    let floor = (parts as u64).checked_mul(DUST_LIMIT).ok_or(TxError::AmountOverflow)?;
+    if total < floor {
+        return Err(TxError::InsufficientFunds { available: total, required: floor });
+    }
+    let spread = total - floor;
 
-use std::str::FromStr;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let weights: Vec<f64> = (0..parts)
+        .map(|_| if jitter > 0.0 { 1.0 + rng.gen_range(-jitter..=jitter) } else { 1.0 })
+        .collect();
+    let weight_sum: f64 = weights.iter().sum();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use bitcoin::secp256k1::{Secp256k1, SecretKey, PublicKey};
-    use crate::address::DogeAddress;
-    use crate::network::Network;
+    let mut chunks = Vec::with_capacity(parts);
+    let mut assigned = 0u64;
+    for w in &weights[..parts - 1] {
+        let extra = ((spread as f64) * w / weight_sum).floor() as u64;
+        let extra = extra.min(spread - assigned);
+        assigned += extra;
+        chunks.push((address.clone(), DUST_LIMIT + extra));
+    }
+    chunks.push((address.clone(), DUST_LIMIT + spread - assigned));
+    Ok(chunks)
+}
 
-    #[test]
+/// Build and sign a tip of `amount` to `to`, with an optional OP_RETURN `memo`.
+///
+/// Coins are taken largest-first from `utxos` (immature coinbase outputs skipped) until
+/// they cover the tip and fee; change that clears `DUST_LIMIT` goes to `change_address`.
+/// Memos longer than `MAX_OP_RETURN_SIZE` bytes are rejected.
+pub fn tip(
+    to: &DogeAddress,
+    amount: u64,
+    memo: Option<&str>,
+    utxos: &[ExplorerUtxo],
+    change_address: &DogeAddress,
+    signer: &dyn Signer,
+    fee_rate: u64,
+) -> Result<Transaction, TxError> {
+    if let Some(memo) = memo.filter(|m| m.len() > MAX_OP_RETURN_SIZE) {
+        return Err(TxError::OpReturnTooLarge(memo.len()));
+    }
+
+    let mut fee = 0u64;
+    loop {
+        let selection = coinselect::select_largest_first(utxos, amount.saturating_add(fee), to.network)
+            .map_err(|e| match e {
+                coinselect::CoinSelectError::InsufficientFunds { needed, available } => {
+                    TxError::InsufficientFunds { available, required: needed }
+                }
+                coinselect::CoinSelectError::AmountOverflow => TxError::AmountOverflow,
+            })?;
+
+        let mut builder = TransactionBuilder::new();
+        let mut prev_scripts = Vec::with_capacity(selection.len());
+        for u in &selection {
+            let prev_script = ScriptBuf::from_bytes(hex::decode(&u.script_hex).map_err(|e| TxError::InvalidHex(e.to_string()))?);
+            builder.add_input_with_prevout(&u.txid, u.vout, &prev_script);
+            prev_scripts.push(prev_script);
+        }
+        builder.add_output(to, amount)?;
+        if let Some(memo) = memo {
+            builder.add_op_return(memo.as_bytes())?;
+        }
+
+        let inputs_total: u64 = selection.iter().map(|u| u.value_satoshis).sum();
+        match builder.solve_change(inputs_total, fee_rate, change_address.script_pubkey().len()) {
+            Some(change) => builder.add_output(change_address, change)?,
+            None => {
+                let needed_fee = builder.estimate_vsize() as u64 * fee_rate;
+                if inputs_total < amount + needed_fee {
+                    // Selection was sized for a smaller fee; retry with the larger target
+                    fee = needed_fee;
+                    continue;
+                }
+            }
+        }
+
+        for (i, prev_script) in prev_scripts.iter().enumerate() {
+            builder.sign_input(i, signer, prev_script)?;
+        }
+        return Ok(builder.build());
+    }
+}
+
+/// Unsigned consolidation of `utxos` into one output to `to`, but only while fees are low.
+///
+/// Returns `None` unless `current_fee_rate` is below `threshold_rate` (both sat/byte), and
+/// when fewer than two UTXOs are worth spending at the current rate or the merged output
+/// would be dust. Immature coinbase outputs and UTXOs without a known script are left alone.
+/// The fee is paid at `current_fee_rate`.
+pub fn auto_consolidate_if_cheap(
+    utxos: &[ExplorerUtxo],
+    to: &DogeAddress,
+    current_fee_rate: u64,
+    threshold_rate: u64,
+) -> Option<TransactionBuilder> {
+    if current_fee_rate >= threshold_rate {
+        return None;
+    }
+
+    let mut builder = TransactionBuilder::new();
+    let mut total = 0u64;
+    for u in utxos {
+        if coinselect::is_immature_coinbase(u, to.network) {
+            continue;
+        }
+        let Some(prev_script) = hex::decode(&u.script_hex).ok().filter(|s| !s.is_empty()).map(ScriptBuf::from_bytes) else {
+            continue;
+        };
+        let spend_fee = script::estimated_input_size(&script::classify(&prev_script)) as u64 * current_fee_rate;
+        if u.value_satoshis <= spend_fee {
+            continue;
+        }
+        builder.add_input_with_prevout(&u.txid, u.vout, &prev_script);
+        total = total.checked_add(u.value_satoshis)?;
+    }
+    if builder.inputs.len() < 2 {
+        return None;
+    }
+
+    // The output value doesn't change the size, so probe with the full total
+    let mut probe = builder.clone();
+    probe.add_output(to, total).ok()?;
+    let fee = probe.estimate_vsize() as u64 * current_fee_rate;
+    let value = total.checked_sub(fee).filter(|v| *v >= DUST_LIMIT)?;
+    builder.add_output(to, value).ok()?;
+    Some(builder)
+}
+
+/// Dust threshold for an output paying `script_pubkey`, given a dust relay fee in sat/kB.
+///
+/// An output is dust when spending it would cost more than it's worth: its value is below
+/// the relay fee for the output plus the input that later spends it. OP_RETURN outputs are
+/// unspendable and never dust.
+pub fn dust_threshold(script_pubkey: &Script, dust_relay_fee: u64) -> u64 {
+    let script_type = script::classify(script_pubkey);
+    if script_type == ScriptType::NullData {
+        return 0;
+    }
+    let output_size = 8 + 1 + script_pubkey.len();
+    let spend_size = output_size + script::estimated_input_size(&script_type);
+    spend_size as u64 * dust_relay_fee / 1000
+}
+
+/// Indices of the outputs in `tx` below their dust threshold at `dust_relay_fee` sat/kB
+pub fn dust_outputs(tx: &Transaction, dust_relay_fee: u64) -> Vec<usize> {
+    tx.output
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| o.value.to_sat() < dust_threshold(&o.script_pubkey, dust_relay_fee))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Fee rate of `tx` in satoshis per weight unit, a quarter of its sat/vbyte rate
+pub fn fee_rate_sat_per_weight(tx: &Transaction, fee_sats: u64) -> f64 {
+    match tx.weight().to_wu() {
+        0 => 0.0,
+        wu => fee_sats as f64 / wu as f64,
+    }
+}
+
+/// Lowest fee a BIP125 replacement of `replacement_vsize` bytes may pay: more than the
+/// original fee, by at least the incremental relay fee (sat/byte) for its own size.
+pub fn min_replacement_fee(original_fee: u64, replacement_vsize: usize, incremental_relay_fee: u64) -> u64 {
+    original_fee.saturating_add((replacement_vsize as u64).saturating_mul(incremental_relay_fee))
+}
+
+/// How long until an output locked with OP_CHECKLOCKTIMEVERIFY to `locktime` can be spent,
+/// or `None` if a spend could go into the next block.
+///
+/// Height locks return remaining blocks: the spend is valid in the first block above
+/// `locktime`. Time locks return remaining seconds: `current_time` should be the tip's
+/// median time past, which must pass `locktime`. Dogecoin targets one block per minute.
+pub fn blocks_until_spendable(locktime: LockTime, current_height: u64, current_time: u32) -> Option<u64> {
+    match locktime {
+        LockTime::Blocks(height) => {
+            let height = u64::from(height.to_consensus_u32());
+            (height > current_height).then(|| height - current_height)
+        }
+        LockTime::Seconds(time) => {
+            let time = time.to_consensus_u32();
+            (time >= current_time).then(|| u64::from(time - current_time) + 1)
+        }
+    }
+}
+
+/// Returns true if the transaction signals opt-in replace-by-fee (BIP125):
+/// at least one input has a sequence number below `0xFFFFFFFE`.
+///
+/// A signaling transaction can be replaced while unconfirmed, so 0-conf payments
+/// that signal RBF should not be trusted.
+pub fn is_rbf_signaled(tx: &Transaction) -> bool {
+    tx.input.iter().any(|i| i.sequence.is_rbf())
+}
+
+/// Compute the txid of a raw transaction hex without deserializing it.
+///
+/// The txid is the double-SHA256 of the serialized bytes (displayed byte-reversed).
+/// SegWit hex is rejected, as in `tx_from_hex`.
+pub fn txid_from_hex(tx_hex: &str) -> Result<Txid, TxError> {
+    let bytes = decode_legacy_hex(tx_hex)?;
+    Ok(Txid::from_raw_hash(sha256d::Hash::hash(&bytes)))
+}
+
+/// Deserialize a raw transaction hex.
+///
+/// SegWit serializations (marker `0x00`, flag `0x01` after the version) are rejected with
+/// `TxError::SegWitNotSupported` instead of being misread, since Bitcoin and Dogecoin
+/// transaction hex are easy to mix up.
+pub fn tx_from_hex(tx_hex: &str) -> Result<Transaction, TxError> {
+    let bytes = decode_legacy_hex(tx_hex)?;
+    bitcoin::consensus::encode::deserialize(&bytes).map_err(|e| TxError::InvalidHex(e.to_string()))
+}
+
+fn decode_legacy_hex(tx_hex: &str) -> Result<Vec<u8>, TxError> {
+    let bytes = hex::decode(tx_hex.trim()).map_err(|e| TxError::InvalidHex(e.to_string()))?;
+    if bytes.len() > 6 && bytes[4] == 0x00 && bytes[5] == 0x01 {
+        return Err(TxError::SegWitNotSupported);
+    }
+    Ok(bytes)
+}
+
+/// Check that a signature (DER + trailing sighash byte) is canonically encoded.
+///
+/// Applies the BIP66 strict-DER rules, requires a low S value (BIP62) and a
+/// defined sighash type (ALL/NONE/SINGLE, optionally with ANYONECANPAY).
+pub fn is_canonical_der(sig_with_sighash: &[u8]) -> bool {
+    let sig = sig_with_sighash;
+    // Format: 0x30 [total-len] 0x02 [R-len] [R] 0x02 [S-len] [S] [sighash]
+    if sig.len() < 9 || sig.len() > 73 {
+        return false;
+    }
+    if sig[0] != 0x30 || sig[1] as usize != sig.len() - 3 {
+        return false;
+    }
+
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return false;
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != sig.len() {
+        return false;
+    }
+
+    // R: positive integer without unnecessary leading zero
+    if sig[2] != 0x02 || len_r == 0 || sig[4] & 0x80 != 0 {
+        return false;
+    }
+    if len_r > 1 && sig[4] == 0x00 && sig[5] & 0x80 == 0 {
+        return false;
+    }
+
+    // S: same rules
+    if sig[len_r + 4] != 0x02 || len_s == 0 || sig[len_r + 6] & 0x80 != 0 {
+        return false;
+    }
+    if len_s > 1 && sig[len_r + 6] == 0x00 && sig[len_r + 7] & 0x80 == 0 {
+        return false;
+    }
+
+    let sighash_type = sig[sig.len() - 1] & !0x80;
+    if !(0x01..=0x03).contains(&sighash_type) {
+        return false;
+    }
+
+    match bitcoin::secp256k1::ecdsa::Signature::from_der(&sig[..sig.len() - 1]) {
+        Ok(parsed) => {
+            let mut normalized = parsed;
+            normalized.normalize_s();
+            normalized == parsed
+        }
+        Err(_) => false,
+    }
+}
+
+/// Break a transaction's serialization into labeled hex ranges.
+///
+/// Returns `(label, hex)` pairs in wire order: version, input count, each input's
+/// outpoint / scriptSig (with its length prefix) / sequence, output count, each
+/// output's value / scriptPubKey, and locktime. Concatenating the hex yields the
+/// full serialized transaction, which makes malformed bytes easy to locate.
+pub fn annotated_hex(tx: &Transaction) -> Vec<(String, String)> {
+    use bitcoin::consensus::encode::{serialize_hex, VarInt};
+
+    let mut parts = Vec::new();
+    parts.push(("version".to_string(), serialize_hex(&tx.version)));
+    parts.push(("input count".to_string(), serialize_hex(&VarInt(tx.input.len() as u64))));
+    for (i, input) in tx.input.iter().enumerate() {
+        parts.push((format!("input[{i}] outpoint"), serialize_hex(&input.previous_output)));
+        parts.push((format!("input[{i}] scriptSig"), serialize_hex(&input.script_sig)));
+        parts.push((format!("input[{i}] sequence"), serialize_hex(&input.sequence)));
+    }
+    parts.push(("output count".to_string(), serialize_hex(&VarInt(tx.output.len() as u64))));
+    for (i, output) in tx.output.iter().enumerate() {
+        parts.push((format!("output[{i}] value"), serialize_hex(&output.value)));
+        parts.push((format!("output[{i}] scriptPubKey"), serialize_hex(&output.script_pubkey)));
+    }
+    parts.push(("locktime".to_string(), serialize_hex(&tx.lock_time)));
+    parts
+}
+
+/// Split a P2PKH scriptSig `<sig+sighash> <pubkey>` into its two pushes.
+///
+/// Minimal push encoding isn't enforced: transactions built elsewhere may push with
+/// `OP_PUSHDATA1/2/4` where a direct push would do, and those are still valid by consensus.
+fn parse_p2pkh_script_sig(script_sig: &ScriptBuf) -> Option<(Vec<u8>, Vec<u8>)> {
+    let pushes: Vec<Vec<u8>> = script_sig
+        .instructions()
+        .map(|ins| match ins {
+            Ok(bitcoin::script::Instruction::PushBytes(b)) => Some(b.as_bytes().to_vec()),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    match <[Vec<u8>; 2]>::try_from(pushes) {
+        Ok([sig, pubkey]) => Some((sig, pubkey)),
+        Err(_) => None,
+    }
+}
+
+// Extract the (message, signature, pubkey) triple a P2PKH input commits to
+fn p2pkh_verification_data(
+    tx: &Transaction,
+    cache: &SighashCache<&Transaction>,
+    input_index: usize,
+    prev_script: &ScriptBuf,
+) -> Option<(Message, bitcoin::secp256k1::ecdsa::Signature, bitcoin::secp256k1::PublicKey)> {
+    let (sig, pubkey) = parse_p2pkh_script_sig(&tx.input.get(input_index)?.script_sig)?;
+    let (&sighash_type, der) = sig.split_last()?;
+    let signature = bitcoin::secp256k1::ecdsa::Signature::from_der(der).ok()?;
+    let public_key = bitcoin::secp256k1::PublicKey::from_slice(&pubkey).ok()?;
+    let sighash = cache
+        .legacy_signature_hash(input_index, prev_script, sighash_type as u32)
+        .ok()?;
+    Some((Message::from_digest(sighash.to_byte_array()), signature, public_key))
+}
+
+/// The P2PKH address that spent `input_index`, recovered from the pubkey in its scriptSig.
+///
+/// Returns `None` if the input doesn't exist or its scriptSig isn't `<sig> <pubkey>`.
+pub fn spender_address(tx: &Transaction, input_index: usize, network: Network) -> Option<DogeAddress> {
+    let (_, pubkey) = parse_p2pkh_script_sig(&tx.input.get(input_index)?.script_sig)?;
+    bitcoin::secp256k1::PublicKey::from_slice(&pubkey).ok()?;
+    // Hash the key exactly as pushed, so uncompressed keys map to their own address
+    let hash = bitcoin::hashes::hash160::Hash::hash(&pubkey);
+    Some(DogeAddress::from_pubkey_hash(hash.as_byte_array(), network))
+}
+
+/// The address each output pays, in output order.
+///
+/// P2PKH and P2SH outputs decode to their address; OP_RETURN, bare P2PK and bare multisig
+/// outputs have no address form and give `None`, as do non-standard scripts.
+pub fn output_addresses(tx: &Transaction, network: Network) -> Vec<Option<DogeAddress>> {
+    tx.output
+        .iter()
+        .map(|output| {
+            let bytes = output.script_pubkey.as_bytes();
+            match script::classify(&output.script_pubkey) {
+                ScriptType::P2pkh => Some(DogeAddress::from_pubkey_hash(bytes[3..23].try_into().ok()?, network)),
+                ScriptType::P2sh => Some(DogeAddress::from_script_hash(bytes[2..22].try_into().ok()?, network)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Verify the P2PKH signature of one input against its prevout scriptPubKey
+pub fn verify_input(tx: &Transaction, input_index: usize, prev_script: &ScriptBuf) -> bool {
+    let secp = Secp256k1::verification_only();
+    let cache = SighashCache::new(tx);
+    match p2pkh_verification_data(tx, &cache, input_index, prev_script) {
+        Some((msg, sig, pk)) => secp.verify_ecdsa(&msg, &sig, &pk).is_ok(),
+        None => false,
+    }
+}
+
+/// Verify every P2PKH input signature, returning the indices of inputs that fail.
+///
+/// `prev_scripts[i]` is the scriptPubKey spent by input `i`. All (message, signature,
+/// pubkey) tuples are collected first and checked against one shared verification
+/// context and sighash cache. libsecp256k1 has no true ECDSA batch verification, so
+/// each signature is still checked individually; the saving is in setup, not math.
+pub fn verify_inputs_batch(tx: &Transaction, prev_scripts: &[ScriptBuf]) -> Result<(), Vec<usize>> {
+    let secp = Secp256k1::verification_only();
+    let cache = SighashCache::new(tx);
+
+    let mut failed = Vec::new();
+    let mut batch = Vec::with_capacity(tx.input.len());
+    for i in 0..tx.input.len() {
+        match prev_scripts.get(i).and_then(|s| p2pkh_verification_data(tx, &cache, i, s)) {
+            Some(item) => batch.push((i, item)),
+            None => failed.push(i),
+        }
+    }
+
+    for (i, (msg, sig, pk)) in batch {
+        if secp.verify_ecdsa(&msg, &sig, &pk).is_err() {
+            failed.push(i);
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        failed.sort_unstable();
+        Err(failed)
+    }
+}
+
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey, PublicKey};
+    use crate::address::DogeAddress;
+    use crate::network::Network;
+
+    /// A confirmed, non-coinbase output of the fixture txid paying `owner`
+    fn utxo(vout: u32, value_satoshis: u64, owner: &DogeAddress) -> ExplorerUtxo {
+        ExplorerUtxo {
+            txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+            vout,
+            value_satoshis,
+            script_hex: hex::encode(owner.script_pubkey().as_bytes()),
+            confirmations: 10,
+            is_coinbase: false,
+        }
+    }
+
+    #[test]
     fn test_transaction_structure() {
         let mut builder = TransactionBuilder::new();
         let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
@@ -194,11 +1564,1235 @@ mod tests {
         let pubkey = PublicKey::from_secret_key(&secp, &secret);
         let address = DogeAddress::from_pubkey(&pubkey, Network::Testnet);
 
-        builder.add_output(&address, 1000);
+        builder.add_output(&address, 1000).unwrap();
 
         let tx = builder.build();
         assert_eq!(tx.input.len(), 1);
         assert_eq!(tx.output.len(), 1);
         assert_eq!(tx.output[0].value.to_sat(), 1000);
     }
+
+    #[test]
+    fn test_split_output_sums_to_total() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        for jitter in [None, Some(0.1), Some(0.5), Some(0.9)] {
+            for (total, parts) in [(100_000_000u64, 3usize), (4 * DUST_LIMIT, 4), (123_456_789, 10), (DUST_LIMIT, 1)] {
+                let chunks = split_output(&address, total, parts, jitter, 7).unwrap();
+                assert_eq!(chunks.len(), parts);
+                assert_eq!(chunks.iter().map(|(_, v)| v).sum::<u64>(), total);
+                assert!(chunks.iter().all(|(a, v)| *a == address && *v >= DUST_LIMIT));
+            }
+        }
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_outputs(&split_output(&address, 3 * DUST_LIMIT + 1000, 3, None, 0).unwrap()).unwrap();
+        let values: Vec<u64> = builder.build().output.iter().map(|o| o.value.to_sat()).collect();
+        assert_eq!(values, vec![DUST_LIMIT + 333, DUST_LIMIT + 333, DUST_LIMIT + 334]);
+
+        // Jittered splits are fixed by the seed
+        let values = |seed| -> Vec<u64> {
+            split_output(&address, 100_000_000, 3, Some(0.2), seed).unwrap().into_iter().map(|(_, v)| v).collect()
+        };
+        assert_eq!(values(42), values(42));
+        assert_eq!(values(42), vec![32_804_320, 33_007_840, 34_187_840]);
+        assert_ne!(values(42), values(43));
+    }
+
+    #[test]
+    fn test_split_output_rejects_sub_dust_chunks() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        assert!(matches!(
+            split_output(&address, 7, 4, None, 0),
+            Err(TxError::InsufficientFunds { available: 7, required }) if required == 4 * DUST_LIMIT
+        ));
+        assert!(split_output(&address, 4 * DUST_LIMIT - 1, 4, Some(0.2), 0).is_err());
+    }
+
+    #[test]
+    fn test_is_rbf_signaled() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        let mut tx = builder.build();
+        assert_eq!(tx.input[0].sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+        assert!(is_rbf_signaled(&tx));
+
+        tx.input[0].sequence = Sequence::MAX;
+        assert!(!is_rbf_signaled(&tx));
+    }
+
+    #[test]
+    fn test_weight_has_no_witness_discount() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 100_000_000).unwrap();
+        let tx = builder.build();
+        let size = bitcoin::consensus::encode::serialize(&tx).len() as u64;
+        assert_eq!(tx.weight().to_wu(), size * 4);
+        assert_eq!(tx.vsize() as u64, size);
+
+        let fee = size * 100;
+        assert_eq!(fee_rate_sat_per_weight(&tx, fee), 25.0);
+    }
+
+    #[test]
+    fn test_validate_max_outputs() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        for i in 0..3u8 {
+            let address = DogeAddress::from_pubkey_hash(&[i; 20], Network::Testnet);
+            builder.add_output(&address, DUST_LIMIT).unwrap();
+        }
+        assert!(builder.validate().is_ok());
+
+        builder.set_max_outputs(3);
+        assert!(builder.validate().is_ok());
+
+        builder.set_max_outputs(2);
+        assert!(matches!(builder.validate(), Err(TxError::TooManyOutputs { count: 3, max: 2 })));
+    }
+
+    #[test]
+    fn test_min_replacement_fee() {
+        let original_fee = 226_000;
+        let fee = min_replacement_fee(original_fee, 226, 100);
+        assert_eq!(fee, 248_600);
+        assert!(fee >= original_fee + 226 * 100);
+        assert_eq!(min_replacement_fee(original_fee, 226, 0), original_fee);
+        assert_eq!(min_replacement_fee(u64::MAX, 226, 100), u64::MAX);
+    }
+
+    #[test]
+    fn test_blocks_until_spendable() {
+        let height_lock = LockTime::from_height(5_000_100).unwrap();
+        assert_eq!(blocks_until_spendable(height_lock, 5_000_000, 0), Some(100));
+        assert_eq!(blocks_until_spendable(height_lock, 5_000_099, 0), Some(1));
+        assert_eq!(blocks_until_spendable(height_lock, 5_000_100, 0), None);
+        assert_eq!(blocks_until_spendable(LockTime::ZERO, 0, 0), None);
+
+        let time_lock = LockTime::from_time(1_700_000_000).unwrap();
+        assert_eq!(blocks_until_spendable(time_lock, 0, 1_699_996_400), Some(3_601));
+        assert_eq!(blocks_until_spendable(time_lock, 0, 1_700_000_000), Some(1));
+        assert_eq!(blocks_until_spendable(time_lock, 0, 1_700_000_001), None);
+    }
+
+    #[test]
+    fn test_output_addresses() {
+        let p2pkh = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let p2sh = DogeAddress::from_script_hash(&[0x22; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&p2pkh, 100_000_000).unwrap();
+        builder.add_op_return(b"memo").unwrap();
+        let tx = builder.clone().build();
+        assert_eq!(output_addresses(&tx, Network::Testnet), vec![Some(p2pkh.clone()), None]);
+
+        builder.add_output(&p2sh, 100_000_000).unwrap();
+        let mut tx = builder.build();
+        tx.output.push(TxOut { value: Amount::from_sat(1), script_pubkey: ScriptBuf::from_bytes(vec![0x51]) });
+        assert_eq!(output_addresses(&tx, Network::Testnet), vec![Some(p2pkh), None, Some(p2sh), None]);
+    }
+
+    #[test]
+    fn test_dust_outputs() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 100).unwrap();
+        builder.add_output(&address, 100_000).unwrap();
+        let tx = builder.build();
+
+        // P2PKH: (34-byte output + 148-byte spend) at 0.001 DOGE/kB
+        assert_eq!(dust_threshold(&tx.output[0].script_pubkey, 100_000), 18_200);
+        assert_eq!(dust_outputs(&tx, 100_000), vec![0]);
+        assert_eq!(dust_outputs(&tx, 0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_from_transaction() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&address, 1000).unwrap();
+        builder.sign_input(0, &secret, &ScriptBuf::new()).unwrap();
+        let signed = builder.build();
+        assert!(!signed.input[0].script_sig.is_empty());
+
+        let mut loaded = TransactionBuilder::from_transaction(&signed);
+        assert_eq!(loaded.inputs().len(), 1);
+        assert_eq!(loaded.inputs()[0].previous_output, signed.input[0].previous_output);
+        assert_eq!(loaded.inputs()[0].sequence, signed.input[0].sequence);
+        assert!(loaded.inputs()[0].script_sig.is_empty());
+        assert_eq!(loaded.outputs(), signed.output.as_slice());
+
+        loaded.add_output(&address, 2000).unwrap();
+        let rebuilt = loaded.build();
+        assert_eq!(rebuilt.version, signed.version);
+        assert_eq!(rebuilt.lock_time, signed.lock_time);
+        assert_eq!(rebuilt.output.len(), 2);
+    }
+
+    #[test]
+    fn test_txid_from_hex() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        builder.sign_input(0, &secret, &ScriptBuf::new()).unwrap();
+        let tx = builder.build();
+
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+        assert_eq!(txid_from_hex(&tx_hex).unwrap(), tx.compute_txid());
+        assert!(matches!(txid_from_hex("zz"), Err(TxError::InvalidHex(_))));
+    }
+
+    #[test]
+    fn test_tx_from_hex_rejects_segwit() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        let mut tx = builder.build();
+
+        let legacy_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+        assert_eq!(tx_from_hex(&legacy_hex).unwrap(), tx);
+
+        tx.input[0].witness.push([0x30; 71]);
+        let segwit_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+        assert_eq!(&segwit_hex[8..12], "0001");
+        assert!(matches!(tx_from_hex(&segwit_hex), Err(TxError::SegWitNotSupported)));
+        assert!(matches!(txid_from_hex(&segwit_hex), Err(TxError::SegWitNotSupported)));
+
+        assert!(matches!(tx_from_hex(&legacy_hex[..20]), Err(TxError::InvalidHex(_))));
+    }
+
+    /// Flip a signature's S to `n - S`, producing the (valid but non-canonical) high-S twin.
+    fn to_high_s(sig: &bitcoin::secp256k1::ecdsa::Signature) -> bitcoin::secp256k1::ecdsa::Signature {
+        const N: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+            0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+        ];
+        let compact = sig.serialize_compact();
+        let mut out = compact;
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let d = N[i] as i16 - compact[32 + i] as i16 - borrow;
+            borrow = if d < 0 { 1 } else { 0 };
+            out[32 + i] = (d + 256 * borrow) as u8;
+        }
+        bitcoin::secp256k1::ecdsa::Signature::from_compact(&out).unwrap()
+    }
+
+    #[test]
+    fn test_apply_signature_canonical_der() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&secp, &secret);
+        let address = DogeAddress::from_pubkey(&pubkey, Network::Testnet);
+        let prev_script = crate::script::p2pkh_script_pubkey(address.pubkey_hash());
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+
+        let digest = builder.legacy_sighash(0, &prev_script).unwrap();
+        let sig = secp.sign_ecdsa(&Message::from_digest(digest), &secret);
+
+        let mut good = sig.serialize_der().to_vec();
+        good.push(0x01);
+        assert!(is_canonical_der(&good));
+
+        let mut high_s = to_high_s(&sig).serialize_der().to_vec();
+        high_s.push(0x01);
+        assert!(!is_canonical_der(&high_s));
+
+        let mut bad_hashtype = good.clone();
+        *bad_hashtype.last_mut().unwrap() = 0x05;
+        assert!(!is_canonical_der(&bad_hashtype));
+
+        assert!(matches!(builder.apply_signature(0, &high_s, &pubkey), Err(TxError::NonCanonicalSignature(0))));
+        assert!(matches!(builder.apply_signature(1, &good, &pubkey), Err(TxError::InputIndexOutOfRange(1))));
+        builder.apply_signature(0, &good, &pubkey).unwrap();
+
+        let mut expected = builder.clone();
+        expected.force_sign_input(0, &secret, &prev_script);
+        assert_eq!(builder.build(), expected.build());
+    }
+
+    #[test]
+    fn test_sign_input_twice_errors() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let prev_script = script::p2pkh_script_pubkey(&[0x11; 20]);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet), 100_000_000).unwrap();
+
+        builder.sign_input(0, &secret, &prev_script).unwrap();
+        let signed = builder.inputs()[0].script_sig.clone();
+        assert!(matches!(builder.sign_input(0, &secret, &prev_script), Err(TxError::AlreadySigned(0))));
+        assert!(matches!(builder.sign_input(1, &secret, &prev_script), Err(TxError::InputIndexOutOfRange(1))));
+        assert_eq!(builder.inputs()[0].script_sig, signed);
+
+        let other = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        builder.force_sign_input(0, &other, &prev_script);
+        assert_ne!(builder.inputs()[0].script_sig, signed);
+    }
+
+    #[test]
+    fn test_apply_signature_detects_sighash_byte() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&secp, &secret);
+        let address = DogeAddress::from_pubkey(&pubkey, Network::Testnet);
+        let prev_script = address.script_pubkey();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        let digest = builder.legacy_sighash(0, &prev_script).unwrap();
+        let der = secp.sign_ecdsa(&Message::from_digest(digest), &secret).serialize_der().to_vec();
+
+        let mut expected = builder.clone();
+        expected.force_sign_input(0, &secret, &prev_script);
+        let expected = expected.build();
+
+        // Without the byte: SIGHASH_ALL is appended
+        let mut without = builder.clone();
+        without.apply_signature(0, &der, &pubkey).unwrap();
+        assert_eq!(without.build(), expected);
+
+        // With the byte: used as-is, not appended twice
+        let mut with_byte = der.clone();
+        with_byte.push(0x01);
+        let mut with = builder.clone();
+        with.apply_signature(0, &with_byte, &pubkey).unwrap();
+        assert_eq!(with.build(), expected);
+
+        *with_byte.last_mut().unwrap() = 0x07;
+        assert!(matches!(
+            builder.apply_signature(0, &with_byte, &pubkey),
+            Err(TxError::InvalidSighashType { input: 0, byte: 0x07 })
+        ));
+
+        let mut double = der.clone();
+        double.extend_from_slice(&[0x01, 0x01]);
+        assert!(matches!(builder.apply_signature(0, &double, &pubkey), Err(TxError::NonCanonicalSignature(0))));
+    }
+
+    #[test]
+    fn test_set_rbf() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        assert!(is_rbf_signaled(&builder.clone().build()));
+
+        builder.set_rbf(false).unwrap();
+        let tx = builder.clone().build();
+        assert!(!is_rbf_signaled(&tx));
+        assert_eq!(tx.input[0].sequence, Sequence::MAX);
+
+        builder.set_lock_time(LockTime::from_height(100).unwrap());
+        let tx = builder.clone().build();
+        assert!(!is_rbf_signaled(&tx));
+        assert_eq!(tx.input[0].sequence, Sequence::ENABLE_LOCKTIME_NO_RBF);
+
+        // The signature must commit to the final sequence
+        let prev_script = ScriptBuf::new();
+        let digest = builder.legacy_sighash(0, &prev_script).unwrap();
+        let expected = SighashCache::new(&tx)
+            .legacy_signature_hash(0, &prev_script, EcdsaSighashType::All.to_u32())
+            .unwrap();
+        assert_eq!(digest, expected.to_byte_array());
+        builder.sign_input(0, &secret, &prev_script).unwrap();
+
+        // Changing the sequences now would invalidate the signature
+        assert!(matches!(builder.set_rbf(true), Err(TxError::AlreadySigned(0))));
+        let tx = builder.build();
+        assert_eq!(tx.input[0].sequence, Sequence::ENABLE_LOCKTIME_NO_RBF);
+    }
+
+    #[test]
+    fn test_annotated_hex() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&address, 1000).unwrap();
+        builder.sign_input(0, &secret, &ScriptBuf::new()).unwrap();
+        let tx = builder.build();
+
+        let parts = annotated_hex(&tx);
+        assert_eq!(parts.len(), 2 + 2 * 3 + 1 + 2 + 1);
+        assert_eq!(parts[0], ("version".to_string(), "01000000".to_string()));
+        assert_eq!(parts[1].1, "02");
+        assert_eq!(parts[4], ("input[0] sequence".to_string(), "fdffffff".to_string()));
+        assert_eq!(parts.last().unwrap().0, "locktime");
+
+        let joined: String = parts.iter().map(|(_, h)| h.as_str()).collect();
+        assert_eq!(joined, bitcoin::consensus::encode::serialize_hex(&tx));
+    }
+
+    #[test]
+    fn test_detect_output_reuse() {
+        let a = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let b = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        assert!(matches!(builder.validate(), Err(TxError::NoInputs)));
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        assert!(matches!(builder.validate(), Err(TxError::NoOutputs)));
+
+        builder.add_output(&a, 1000).unwrap();
+        builder.add_output(&b, 2000).unwrap();
+        assert!(builder.detect_output_reuse().is_empty());
+        assert!(builder.validate().unwrap().is_empty());
+
+        builder.add_output(&a, 3000).unwrap();
+        assert_eq!(builder.detect_output_reuse(), vec![(0, 2)]);
+        assert_eq!(builder.validate().unwrap(), vec![TxWarning::OutputReuse(0, 2)]);
+    }
+
+    #[test]
+    fn test_validate_money_range() {
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+
+        // 30M DOGE is above Bitcoin's 21M cap but fine for Dogecoin
+        builder.add_output(&to, 30_000_000 * 100_000_000).unwrap();
+        assert!(builder.validate().is_ok());
+
+        builder.add_output(&to, Network::Testnet.max_money_sats()).unwrap();
+        assert!(matches!(builder.validate(), Err(TxError::AmountOverflow)));
+    }
+
+    #[test]
+    fn test_vsize_estimates() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        for vout in 0..10 {
+            builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", vout);
+        }
+        builder.add_output(&address, 1000).unwrap();
+
+        assert_eq!(builder.estimate_vsize(), estimate_p2pkh_size(10, 1));
+        assert_eq!(builder.worst_case_vsize(), builder.estimate_vsize() + 10);
+
+        // Signed inputs are measured, not estimated
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        for i in 0..10 {
+            builder.sign_input(i, &secret, &ScriptBuf::new()).unwrap();
+        }
+        let actual = builder.clone().build().total_size();
+        assert_eq!(builder.estimate_vsize(), actual);
+        assert_eq!(builder.worst_case_vsize(), actual);
+
+        let tx = builder.clone().build();
+        assert_eq!(builder.vbytes(), actual);
+        assert_eq!(builder.weight(), builder.vbytes() * 4);
+        assert_eq!(builder.weight(), tx.weight().to_wu() as usize);
+    }
+
+    #[test]
+    fn test_vsize_uses_prevout_script_type() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let pubkeys = vec![vec![0x02u8; 33], vec![0x03u8; 33], vec![0x02u8; 33]];
+        let redeem = crate::script::multisig_redeem_script(2, &pubkeys).unwrap();
+
+        let mut p2pkh = TransactionBuilder::new();
+        p2pkh.add_input_with_prevout(txid, 0, &address.script_pubkey());
+        p2pkh.add_output(&address, 1000).unwrap();
+        assert_eq!(p2pkh.estimate_vsize(), estimate_p2pkh_size(1, 1));
+
+        let mut multisig = TransactionBuilder::new();
+        multisig.add_input_with_prevout(txid, 0, &crate::script::p2sh_script_pubkey(&redeem));
+        multisig.add_output(&address, 1000).unwrap();
+        multisig
+            .set_input_script_type(0, crate::script::classify_redeem_script(&redeem))
+            .unwrap();
+        assert_eq!(multisig.estimate_vsize(), TX_OVERHEAD_SIZE + 297 + P2PKH_OUTPUT_SIZE);
+        assert!(multisig.estimate_vsize() > p2pkh.estimate_vsize());
+        assert!(multisig.set_input_script_type(1, ScriptType::P2pkh).is_err());
+    }
+
+    #[test]
+    fn test_verify_inputs_batch() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let prev_script = crate::script::p2pkh_script_pubkey(address.pubkey_hash());
+
+        let mut builder = TransactionBuilder::new();
+        for vout in 0..3 {
+            builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", vout);
+        }
+        builder.add_output(&address, 1000).unwrap();
+        for i in 0..3 {
+            builder.sign_input(i, &secret, &prev_script).unwrap();
+        }
+        let tx = builder.build();
+        let prev_scripts = vec![prev_script.clone(); 3];
+
+        assert!(verify_inputs_batch(&tx, &prev_scripts).is_ok());
+        assert!(verify_input(&tx, 1, &prev_script));
+
+        // Corrupt input 1's signature and drop input 2's prevout
+        let mut bad = tx.clone();
+        bad.input[1].script_sig = tx.input[0].script_sig.clone();
+        assert_eq!(verify_inputs_batch(&bad, &prev_scripts[..2]), Err(vec![1, 2]));
+        assert!(!verify_input(&bad, 1, &prev_script));
+        assert!(!verify_input(&bad, 7, &prev_script));
+    }
+
+    #[test]
+    fn test_sign_inputs_out_of_order() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let prev_script = crate::script::p2pkh_script_pubkey(address.pubkey_hash());
+
+        let mut unsigned = TransactionBuilder::new();
+        unsigned.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        unsigned.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        unsigned.add_output(&address, 1000).unwrap();
+
+        let mut forward = unsigned.clone();
+        forward.sign_input(0, &secret, &prev_script).unwrap();
+        forward.sign_input(1, &secret, &prev_script).unwrap();
+
+        let mut reverse = unsigned;
+        reverse.sign_input(1, &secret, &prev_script).unwrap();
+        reverse.sign_input(0, &secret, &prev_script).unwrap();
+
+        let forward = forward.build();
+        let reverse = reverse.build();
+        assert!(verify_inputs_batch(&reverse, &[prev_script.clone(), prev_script]).is_ok());
+        // RFC6979 signatures are deterministic, so identical digests give identical transactions
+        assert_eq!(forward, reverse);
+    }
+
+    #[test]
+    fn test_sign_all_inputs_by_outpoint() {
+        let secp = Secp256k1::new();
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let keys: Vec<SecretKey> = [[0x01; 32], [0x02; 32]].iter().map(|k| SecretKey::from_slice(k).unwrap()).collect();
+        let scripts: Vec<ScriptBuf> = keys
+            .iter()
+            .map(|k| DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, k), Network::Testnet).script_pubkey())
+            .collect();
+
+        // Inputs added in the opposite order to the map's natural pairing
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(txid, 1);
+        builder.add_input(txid, 0);
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet), 1_000_000).unwrap();
+        let unsigned = builder.clone();
+
+        let outpoint = |vout| OutPoint::new(Txid::from_str(txid).unwrap(), vout);
+        let mut map = HashMap::new();
+        map.insert(outpoint(0), (&keys[0] as &dyn Signer, scripts[0].clone()));
+        assert!(matches!(builder.sign_all_inputs(&map), Err(TxError::MissingSigningKey(o)) if o == outpoint(1)));
+        assert!(builder.inputs().iter().all(|i| i.script_sig.is_empty()));
+
+        map.insert(outpoint(1), (&keys[1] as &dyn Signer, scripts[1].clone()));
+        builder.sign_all_inputs(&map).unwrap();
+        let tx = builder.clone().build();
+        assert!(verify_inputs_batch(&tx, &[scripts[1].clone(), scripts[0].clone()]).is_ok());
+        assert!(matches!(builder.sign_all_inputs(&map), Err(TxError::AlreadySigned(0))));
+
+        let mut positional = unsigned;
+        positional.sign_input(0, &keys[1], &scripts[1]).unwrap();
+        positional.sign_input(1, &keys[0], &scripts[0]).unwrap();
+        assert_eq!(positional.build(), tx);
+    }
+
+    #[test]
+    fn test_build_with_fee_source() {
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let change = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input(txid, 0);
+        builder.add_input(txid, 1);
+        builder.add_output(&to, 400_000_000).unwrap();
+
+        // Input 1 (3 DOGE) goes to the payment; input 0 covers the last 1 DOGE plus the fee
+        let tx = builder.clone().build_with_fee_source(0, &[500_000_000, 300_000_000], 1_000_000, &change).unwrap();
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[1].script_pubkey, change.script_pubkey());
+        assert_eq!(tx.output[1].value.to_sat(), 500_000_000 - 100_000_000 - 1_000_000);
+
+        assert!(matches!(
+            builder.clone().build_with_fee_source(0, &[500_000_000, 450_000_000], 1_000_000, &change),
+            Err(TxError::FeeSourceNotNeeded { surplus: 50_000_000 })
+        ));
+        assert!(matches!(
+            builder.clone().build_with_fee_source(1, &[500_000_000, 300_000_000], 1_000_000, &change),
+            Err(TxError::FeeSourceNotNeeded { .. })
+        ));
+        assert!(matches!(
+            builder.clone().build_with_fee_source(0, &[50_000_000, 300_000_000], 1_000_000, &change),
+            Err(TxError::InsufficientFunds { available: 50_000_000, required: 101_000_000 })
+        ));
+        assert!(matches!(
+            builder.clone().build_with_fee_source(2, &[1, 2], 0, &change),
+            Err(TxError::InputIndexOutOfRange(2))
+        ));
+        assert!(matches!(builder.build_with_fee_source(0, &[1], 0, &change), Err(TxError::PrevoutCountMismatch { .. })));
+    }
+
+    #[test]
+    fn test_add_change_with_fee() {
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let change = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+
+        let mut with_change = builder.clone();
+        assert_eq!(with_change.add_change_with_fee(&change, 300_000_000, 1_000_000).unwrap(), Some(199_000_000));
+        let tx = with_change.build();
+        assert_eq!(tx.output.len(), 2);
+        assert_eq!(tx.output[1].value.to_sat(), 199_000_000);
+        assert_eq!(tx.output[1].script_pubkey, change.script_pubkey());
+
+        // Dust change is absorbed into the fee
+        let mut dusty = builder.clone();
+        assert_eq!(dusty.add_change_with_fee(&change, 101_500_000, 1_000_000).unwrap(), None);
+        assert_eq!(dusty.outputs().len(), 1);
+
+        assert!(matches!(
+            builder.add_change_with_fee(&change, 100_500_000, 1_000_000),
+            Err(TxError::InsufficientFunds { available: 100_500_000, required: 101_000_000 })
+        ));
+    }
+
+    #[test]
+    fn test_verify_against_node() {
+        use crate::test_util::mock_server;
+        use serde_json::json;
+
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 100_000).unwrap();
+        let tx = builder.clone().build();
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(&tx);
+
+        let matching = json!({ "result": { "txid": tx.compute_txid().to_string(), "hex": tx_hex, "size": tx.total_size() }, "error": null, "id": 1 });
+        let wrong_txid = json!({ "result": { "txid": "00".repeat(32), "size": tx.total_size() }, "error": null, "id": 1 });
+        let wrong_hex = json!({ "result": { "txid": tx.compute_txid().to_string(), "hex": "00" }, "error": null, "id": 1 });
+        let (url, requests) = mock_server(vec![matching.to_string(), wrong_txid.to_string(), wrong_hex.to_string()]);
+        let rpc = DogeRpcClient::new(&url, None, None);
+
+        builder.verify_against_node(&rpc).unwrap();
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(request["method"], "decoderawtransaction");
+        assert_eq!(request["params"][0], tx_hex);
+
+        assert!(builder.verify_against_node(&rpc).is_err());
+        assert!(builder.verify_against_node(&rpc).is_err());
+    }
+
+    #[test]
+    fn test_build_deterministic() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let utxos = vec![
+            utxo(1, 200_000_000, &from),
+            ExplorerUtxo {
+                txid: "0b48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+                ..utxo(0, 200_000_000, &from)
+            },
+        ];
+        let recipients = vec![
+            (DogeAddress::from_pubkey_hash(&[0x33; 20], Network::Testnet), 150_000_000),
+            (DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet), 50_000_000),
+        ];
+
+        let build = |utxos: &[ExplorerUtxo]| {
+            let tx = TransactionBuilder::build_deterministic(utxos, &recipients, &from, 1_000, true, &secret).unwrap();
+            bitcoin::consensus::encode::serialize_hex(&tx)
+        };
+        let first = build(&utxos);
+        assert_eq!(first, build(&utxos));
+
+        // BIP69 makes the input order irrelevant
+        let reversed: Vec<ExplorerUtxo> = utxos.iter().rev().cloned().collect();
+        assert_eq!(first, build(&reversed));
+
+        let tx: Transaction = bitcoin::consensus::encode::deserialize_hex(&first).unwrap();
+        assert!(tx.input[0].previous_output.txid.to_string() < tx.input[1].previous_output.txid.to_string());
+        assert!(tx.output.windows(2).all(|w| w[0].value <= w[1].value));
+        assert!((0..2).all(|i| verify_input(&tx, i, &from.script_pubkey())));
+
+        assert!(matches!(
+            TransactionBuilder::build_deterministic(&utxos[..1], &recipients, &from, 1_000, true, &secret),
+            Err(TxError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_solve_change() {
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+
+        let change = builder.solve_change(300_000_000, 1_000, 25).unwrap();
+        let fee = 300_000_000 - 100_000_000 - change;
+        let size = estimate_p2pkh_size(1, 2) as u64;
+        assert_eq!(fee, size * 1_000);
+        assert!(fee >= size * 1_000);
+
+        // Inputs can't cover the outputs plus fee, or the leftover is dust
+        assert_eq!(builder.solve_change(100_000_000, 1_000, 25), None);
+        assert_eq!(builder.solve_change(100_000_000 + size * 1_000 + 10, 1_000, 25), None);
+    }
+
+    #[test]
+    fn test_build_with_change_split() {
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let change_a = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let change_b = DogeAddress::from_pubkey_hash(&[0x33; 20], Network::Testnet);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+        let total_input = 300_000_001;
+
+        let tx = builder
+            .clone()
+            .build_with_change_split(&[change_a.clone(), change_b.clone()], total_input, 1_000)
+            .unwrap();
+        let fee = estimate_p2pkh_size(1, 3) as u64 * 1_000;
+        assert_eq!(tx.output.len(), 3);
+        assert_eq!(tx.output[1].script_pubkey, change_a.script_pubkey());
+        assert_eq!(tx.output[2].script_pubkey, change_b.script_pubkey());
+        assert!(tx.output[1].value.to_sat() - tx.output[2].value.to_sat() <= 1);
+        let out_total: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        assert_eq!(out_total + fee, total_input);
+
+        // Two shares would be dust, one output still clears the limit
+        let total_input = 100_000_000 + estimate_p2pkh_size(1, 3) as u64 * 1_000 + 1_500_000;
+        let tx = builder
+            .clone()
+            .build_with_change_split(&[change_a.clone(), change_b], total_input, 1_000)
+            .unwrap();
+        assert_eq!(tx.output.len(), 2);
+        let out_total: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        assert_eq!(out_total + estimate_p2pkh_size(1, 2) as u64 * 1_000, total_input);
+
+        assert!(matches!(
+            builder.build_with_change(&change_a, 100_000_000, 1_000),
+            Err(TxError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_non_minimal_push_script_sig() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &secret), Network::Testnet);
+        let prev_script = address.script_pubkey();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 100_000).unwrap();
+        builder.sign_input(0, &secret, &prev_script).unwrap();
+        let signed = builder.build();
+        let (sig, pubkey) = parse_p2pkh_script_sig(&signed.input[0].script_sig).unwrap();
+
+        // OP_PUSHDATA1 <sig>, OP_PUSHDATA2 <pubkey>
+        let mut pushdata12 = vec![0x4c, sig.len() as u8];
+        pushdata12.extend_from_slice(&sig);
+        pushdata12.extend_from_slice(&[0x4d, pubkey.len() as u8, 0x00]);
+        pushdata12.extend_from_slice(&pubkey);
+
+        // OP_PUSHDATA4 <sig>, direct push <pubkey>
+        let mut pushdata4 = vec![0x4e, sig.len() as u8, 0x00, 0x00, 0x00];
+        pushdata4.extend_from_slice(&sig);
+        pushdata4.push(pubkey.len() as u8);
+        pushdata4.extend_from_slice(&pubkey);
+
+        for raw in [pushdata12, pushdata4] {
+            let mut tx = signed.clone();
+            tx.input[0].script_sig = ScriptBuf::from_bytes(raw);
+            assert_ne!(tx.input[0].script_sig, signed.input[0].script_sig);
+            assert_eq!(parse_p2pkh_script_sig(&tx.input[0].script_sig), Some((sig.clone(), pubkey.clone())));
+            assert!(verify_input(&tx, 0, &prev_script));
+            assert_eq!(spender_address(&tx, 0, Network::Testnet), Some(address.clone()));
+        }
+
+        let mut garbage = signed.clone();
+        garbage.input[0].script_sig = ScriptBuf::from_bytes(vec![0x4c, 0x05, 0x01]);
+        assert_eq!(spender_address(&garbage, 0, Network::Testnet), None);
+    }
+
+    #[test]
+    fn test_sign_sh_pkh_input() {
+        let secret = SecretKey::from_slice(b"12345678901234567890123456789012").unwrap();
+        let pubkey = secret.public_key(&Secp256k1::new());
+        let redeem = script::sh_pkh_redeem_script(hash160::Hash::hash(&pubkey.serialize()).as_byte_array());
+        let address = script::p2sh_address(&redeem, Network::Testnet);
+        assert_eq!(address.kind(), crate::address::AddressKind::P2sh);
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input_with_prevout("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0, &address.script_pubkey());
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet), 100_000_000).unwrap();
+        builder.set_input_script_type(0, script::classify_redeem_script(&redeem)).unwrap();
+        let estimated = builder.estimate_vsize();
+        builder.sign_sh_pkh_input(0, &secret).unwrap();
+        let tx = builder.build();
+        // The estimate includes the redeem script push, up to signature length variance
+        assert!(estimated.abs_diff(tx.vsize()) <= 1);
+
+        let pushes: Vec<Vec<u8>> = tx.input[0]
+            .script_sig
+            .instructions()
+            .map(|i| i.unwrap().push_bytes().unwrap().as_bytes().to_vec())
+            .collect();
+        assert_eq!(pushes.len(), 3);
+        assert_eq!(pushes[1], pubkey.serialize());
+        assert_eq!(pushes[2], redeem.as_bytes());
+
+        let prevout = TxOut { value: Amount::from_sat(200_000_000), script_pubkey: address.script_pubkey() };
+        crate::interpreter::validate_scripts(&tx, &[prevout]).unwrap();
+    }
+
+    #[test]
+    fn test_sign_mixed_p2pkh_and_multisig() {
+        let secp = Secp256k1::new();
+        let single = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &single), Network::Testnet);
+        let p2pkh_script = address.script_pubkey();
+
+        let cosigners: Vec<SecretKey> = (1..=3u8).map(|i| SecretKey::from_slice(&[i; 32]).unwrap()).collect();
+        let pubkeys: Vec<Vec<u8>> = cosigners
+            .iter()
+            .map(|k| PublicKey::from_secret_key(&secp, k).serialize().to_vec())
+            .collect();
+        let redeem = crate::script::multisig_redeem_script(2, &pubkeys).unwrap();
+        let signers: Vec<&dyn Signer> = cosigners[..2].iter().map(|k| k as &dyn Signer).collect();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&address, 100_000).unwrap();
+        builder
+            .sign_mixed(&[
+                InputSignSpec::P2shMultisig { input_index: 1, signers: &signers, redeem_script: &redeem },
+                InputSignSpec::P2pkh { input_index: 0, signer: &single, prev_script: &p2pkh_script },
+            ])
+            .unwrap();
+        let tx = builder.clone().build();
+
+        assert!(verify_input(&tx, 0, &p2pkh_script));
+
+        // OP_0 <sig1> <sig2> <redeem>
+        let pushes: Vec<Vec<u8>> = tx.input[1]
+            .script_sig
+            .instructions()
+            .map(|i| match i.unwrap() {
+                bitcoin::script::Instruction::PushBytes(b) => b.as_bytes().to_vec(),
+                op => panic!("unexpected {op:?}"),
+            })
+            .collect();
+        assert_eq!(pushes.len(), 4);
+        assert_eq!(pushes[3], redeem.as_bytes());
+        let msg = Message::from_digest(builder.legacy_sighash(1, &redeem).unwrap());
+        for (sig, pubkey) in pushes[1..3].iter().zip(&pubkeys) {
+            let sig = bitcoin::secp256k1::ecdsa::Signature::from_der(&sig[..sig.len() - 1]).unwrap();
+            let pubkey = PublicKey::from_slice(pubkey).unwrap();
+            assert!(secp.verify_ecdsa(&msg, &sig, &pubkey).is_ok());
+        }
+
+        assert!(matches!(
+            builder.sign_mixed(&[InputSignSpec::P2pkh { input_index: 2, signer: &single, prev_script: &p2pkh_script }]),
+            Err(TxError::InputIndexOutOfRange(2))
+        ));
+    }
+
+    #[test]
+    fn test_sign_mixed_is_all_or_nothing() {
+        let secp = Secp256k1::new();
+        let single = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&secp, &single), Network::Testnet);
+        let p2pkh_script = address.script_pubkey();
+        let cosigners: Vec<SecretKey> = (1..=2u8).map(|i| SecretKey::from_slice(&[i; 32]).unwrap()).collect();
+        let pubkeys: Vec<Vec<u8>> = cosigners
+            .iter()
+            .map(|k| PublicKey::from_secret_key(&secp, k).serialize().to_vec())
+            .collect();
+        let redeem = crate::script::multisig_redeem_script(2, &pubkeys).unwrap();
+        let signers: Vec<&dyn Signer> = cosigners.iter().map(|k| k as &dyn Signer).collect();
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 2);
+        builder.add_output(&address, 100_000).unwrap();
+        builder.sign_input(2, &single, &p2pkh_script).unwrap();
+        let signed_before = builder.inputs()[2].script_sig.clone();
+
+        // The multisig spec comes first but must not be applied when a later spec is invalid
+        let multisig = InputSignSpec::P2shMultisig { input_index: 0, signers: &signers, redeem_script: &redeem };
+        assert!(matches!(
+            builder.sign_mixed(&[
+                multisig,
+                InputSignSpec::P2pkh { input_index: 2, signer: &single, prev_script: &p2pkh_script },
+            ]),
+            Err(TxError::AlreadySigned(2))
+        ));
+        let multisig = InputSignSpec::P2shMultisig { input_index: 0, signers: &signers, redeem_script: &redeem };
+        assert!(matches!(
+            builder.sign_mixed(&[
+                multisig,
+                InputSignSpec::P2pkh { input_index: 1, signer: &single, prev_script: &p2pkh_script },
+                InputSignSpec::P2pkh { input_index: 1, signer: &single, prev_script: &p2pkh_script },
+            ]),
+            Err(TxError::AlreadySigned(1))
+        ));
+        assert!(builder.inputs()[..2].iter().all(|i| i.script_sig.is_empty()));
+        assert_eq!(builder.inputs()[2].script_sig, signed_before);
+
+        assert!(matches!(
+            builder.sign_input_p2sh_multisig(2, &signers, &redeem),
+            Err(TxError::AlreadySigned(2))
+        ));
+    }
+
+    #[test]
+    fn test_fold_change_into_output() {
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&to, 1000).unwrap();
+
+        builder.fold_change_into_output(0, 500).unwrap();
+        assert_eq!(builder.outputs().len(), 1);
+        assert_eq!(builder.outputs()[0].value.to_sat(), 1500);
+
+        assert!(matches!(builder.fold_change_into_output(1, 500), Err(TxError::OutputIndexOutOfRange(1))));
+        assert!(matches!(builder.fold_change_into_output(0, u64::MAX), Err(TxError::AmountOverflow)));
+        assert_eq!(builder.outputs()[0].value.to_sat(), 1500);
+    }
+
+    #[test]
+    fn test_add_output_str_p2sh() {
+        let script_hash = [0x33; 20];
+        let address = DogeAddress::from_script_hash(&script_hash, Network::Testnet).to_string();
+        assert!(address.starts_with('2'));
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_output_str(&address, 1_000_000).unwrap();
+        let mut expected = vec![0xa9, 0x14];
+        expected.extend_from_slice(&script_hash);
+        expected.push(0x87);
+        assert_eq!(builder.outputs()[0].script_pubkey.as_bytes(), &expected[..]);
+        assert!(builder.outputs()[0].script_pubkey.is_p2sh());
+
+        assert!(builder.add_output_str("not an address", 1_000_000).is_err());
+        assert_eq!(builder.outputs().len(), 1);
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&to, 100_000_000).unwrap();
+        builder.sign_input(0, &secret, &to.script_pubkey()).unwrap();
+
+        let bytes = builder.to_bytes();
+        let tx = builder.build();
+        assert_eq!(hex::encode(&bytes), bitcoin::consensus::encode::serialize_hex(&tx));
+        assert_eq!(bytes.len(), tx.total_size());
+    }
+
+    #[test]
+    fn test_to_psbt() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 1);
+        builder.add_output(&to, 100_000_000).unwrap();
+        let prevouts = vec![(from.script_pubkey(), 150_000_000)];
+
+        let bytes = builder.to_psbt(&prevouts).unwrap();
+        assert_eq!(&bytes[..5], b"psbt\xff");
+        let psbt = bitcoin::Psbt::deserialize(&bytes).unwrap();
+        assert_eq!(psbt.unsigned_tx, builder.clone().build());
+        let (key, value) = psbt.inputs[0].proprietary.iter().next().unwrap();
+        assert_eq!(key.prefix, PSBT_PREVOUT_PREFIX);
+        let prevout: TxOut = bitcoin::consensus::encode::deserialize(value).unwrap();
+        assert_eq!(prevout, TxOut { value: Amount::from_sat(150_000_000), script_pubkey: from.script_pubkey() });
+
+        assert!(matches!(builder.to_psbt(&[]), Err(TxError::PrevoutCountMismatch { inputs: 1, prevouts: 0 })));
+        builder.sign_input(0, &secret, &from.script_pubkey()).unwrap();
+        assert!(matches!(builder.to_psbt(&prevouts), Err(TxError::AlreadySigned(0))));
+    }
+
+    #[test]
+    fn test_add_output_rejects_zero() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        assert!(matches!(builder.add_output(&address, 0), Err(TxError::ZeroValueOutput)));
+        assert!(matches!(
+            builder.add_outputs(&[(address.clone(), 1_000), (address.clone(), 0)]),
+            Err(TxError::ZeroValueOutput)
+        ));
+        assert!(builder.outputs().is_empty());
+
+        builder.add_op_return(b"zero value is fine here").unwrap();
+        builder.add_output(&address, 1).unwrap();
+        assert_eq!(builder.outputs().len(), 2);
+    }
+
+    #[test]
+    fn test_add_anchor_output() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&secp, &secret);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet), 100_000_000).unwrap();
+
+        assert_eq!(builder.add_anchor_output(&pubkey), 1);
+        let anchor = &builder.outputs()[1];
+        assert_eq!(anchor.value.to_sat(), ANCHOR_VALUE);
+        assert_eq!(anchor.script_pubkey, DogeAddress::from_pubkey(&pubkey, Network::Testnet).script_pubkey());
+        assert!(builder.validate().is_ok());
+        assert!(dust_outputs(&builder.build(), 1_000_000).is_empty());
+    }
+
+    #[test]
+    fn test_add_op_return() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_op_return(b"much wow").unwrap();
+        let output = &builder.outputs()[0];
+        assert_eq!(output.value.to_sat(), 0);
+        assert!(output.script_pubkey.is_op_return());
+        assert_eq!(script::classify(&output.script_pubkey), ScriptType::NullData);
+
+        builder.add_op_return(&[0u8; MAX_OP_RETURN_SIZE]).unwrap();
+        assert!(matches!(builder.add_op_return(&[0u8; MAX_OP_RETURN_SIZE + 1]), Err(TxError::OpReturnTooLarge(81))));
+        assert_eq!(builder.outputs().len(), 2);
+    }
+
+    #[test]
+    fn test_auto_consolidate_if_cheap() {
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let owner = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let utxos = vec![utxo(0, 2_000_000, &owner), utxo(1, 3_000_000, &owner), utxo(2, 10_000, &owner)];
+
+        assert!(auto_consolidate_if_cheap(&utxos, &to, 1_000, 1_000).is_none());
+        assert!(auto_consolidate_if_cheap(&utxos, &to, 5_000, 1_000).is_none());
+
+        // At 100 sat/byte the 10_000-sat UTXO costs more to spend than it holds
+        let builder = auto_consolidate_if_cheap(&utxos, &to, 100, 1_000).unwrap();
+        let fee = builder.estimate_vsize() as u64 * 100;
+        let tx = builder.build();
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value.to_sat(), 5_000_000 - fee);
+
+        assert!(auto_consolidate_if_cheap(&utxos[..1], &to, 100, 1_000).is_none());
+    }
+
+    #[test]
+    fn test_tip() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let from = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let to = DogeAddress::from_pubkey_hash(&[0x33; 20], Network::Testnet);
+        let utxos = vec![utxo(0, 300_000_000, &from), utxo(1, 100_000_000, &from), utxo(2, 50_000_000, &from)];
+
+        let tx = tip(&to, 350_000_000, Some("thanks for the tutorial!"), &utxos, &from, &secret, 1_000).unwrap();
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output.len(), 3);
+        assert_eq!(tx.output[0].value.to_sat(), 350_000_000);
+        assert_eq!(&tx.output[1].script_pubkey.as_bytes()[2..], b"thanks for the tutorial!");
+        assert_eq!(tx.output[2].script_pubkey, from.script_pubkey());
+        let fee = 400_000_000 - tx.output.iter().map(|o| o.value.to_sat()).sum::<u64>();
+        assert!(fee >= tx.total_size() as u64 * 1_000);
+        assert!((0..2).all(|i| verify_input(&tx, i, &from.script_pubkey())));
+
+        let tx = tip(&to, 100_000_000, None, &utxos, &from, &secret, 1_000).unwrap();
+        assert_eq!(tx.output.len(), 2);
+
+        let long_memo = "w".repeat(81);
+        assert!(matches!(tip(&to, 100_000_000, Some(&long_memo), &utxos, &from, &secret, 1_000), Err(TxError::OpReturnTooLarge(81))));
+        assert!(matches!(tip(&to, 450_000_000, None, &utxos, &from, &secret, 1_000), Err(TxError::InsufficientFunds { .. })));
+    }
+
+    #[test]
+    fn test_add_split_change() {
+        let change = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let split = |total, seed| {
+            let mut builder = TransactionBuilder::new();
+            builder.add_split_change(&change, total, seed).unwrap();
+            builder.outputs().iter().map(|o| o.value.to_sat()).collect::<Vec<_>>()
+        };
+
+        let parts = split(500_000_000, 42);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts.iter().sum::<u64>(), 500_000_000);
+        assert!(parts.iter().all(|&p| p >= DUST_LIMIT));
+        assert_eq!(split(500_000_000, 42), parts);
+        assert_ne!(split(500_000_000, 43), parts);
+
+        assert_eq!(split(2 * DUST_LIMIT, 7), vec![DUST_LIMIT, DUST_LIMIT]);
+        assert_eq!(split(2 * DUST_LIMIT - 1, 7), vec![2 * DUST_LIMIT - 1]);
+    }
+
+    #[test]
+    fn test_bump_fee() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let to = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let change = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&to, 100_000_000).unwrap();
+        builder.add_output(&change, 50_000_000).unwrap();
+        builder.sign_input(0, &secret, &ScriptBuf::new()).unwrap();
+        let original = builder.build();
+
+        let bump = |original: &Transaction, change_index, additional_fee| {
+            TransactionBuilder::bump_fee(original, change_index, additional_fee, 1_000_000, 100)
+        };
+        let bumped = bump(&original, 1, 300_000).unwrap();
+        assert!(bumped.inputs()[0].script_sig.is_empty());
+        assert_eq!(bumped.outputs()[0].value.to_sat(), 100_000_000);
+        assert_eq!(bumped.outputs()[1].value.to_sat(), 49_700_000);
+
+        // BIP125: the bump must cover the incremental relay fee for the replacement's size
+        let min_bump = bumped.worst_case_vsize() as u64 * 100;
+        assert!(bump(&original, 1, min_bump).is_ok());
+        assert!(matches!(
+            bump(&original, 1, min_bump - 1),
+            Err(TxError::ReplacementFeeTooLow { fee, required }) if fee == 1_000_000 + min_bump - 1 && required == 1_000_000 + min_bump
+        ));
+        assert!(matches!(bump(&original, 1, 0), Err(TxError::ReplacementFeeTooLow { .. })));
+
+        assert!(matches!(bump(&original, 2, 300_000), Err(TxError::OutputIndexOutOfRange(2))));
+        assert!(matches!(
+            bump(&original, 1, 49_500_000),
+            Err(TxError::InsufficientFunds { available: 50_000_000, required: 50_500_000 })
+        ));
+
+        let mut final_tx = original;
+        final_tx.input[0].sequence = Sequence::MAX;
+        assert!(matches!(bump(&final_tx, 1, 300_000), Err(TxError::NotReplaceable)));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_sign_input_traces_sighash() {
+        use std::fmt::Write as _;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // Collects every event's fields as "name=value" lines
+        struct Capture(Arc<Mutex<String>>);
+
+        struct Fields<'a>(&'a mut String);
+
+        impl Visit for Fields<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let _ = write!(self.0, "{}={:?} ", field.name(), value);
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut out = self.0.lock().unwrap();
+                event.record(&mut Fields(&mut out));
+                out.push('\n');
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let prev_script = address.script_pubkey();
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        let sighash = hex::encode(builder.legacy_sighash(0, &prev_script).unwrap());
+
+        let log = Arc::new(Mutex::new(String::new()));
+        tracing::subscriber::with_default(Capture(log.clone()), || builder.sign_input(0, &secret, &prev_script).unwrap());
+
+        let log = log.lock().unwrap();
+        assert!(log.contains(&format!("sighash={sighash}")));
+        assert!(log.contains("der="));
+        assert!(log.contains("script_sig_len="));
+    }
+
+    #[test]
+    fn test_lock_time_ineffective() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+        builder.add_output(&address, 1000).unwrap();
+        builder.set_lock_time(LockTime::from_height(5_000_000).unwrap());
+        assert!(builder.validate().is_ok());
+
+        // Loading a transaction whose inputs are all final keeps those sequences
+        let mut final_tx = builder.clone().build();
+        final_tx.input[0].sequence = Sequence::MAX;
+        let loaded = TransactionBuilder::from_transaction(&final_tx);
+        assert!(matches!(loaded.validate(), Err(TxError::LockTimeIneffective)));
+
+        let mut unlocked = loaded.clone();
+        unlocked.set_lock_time(LockTime::ZERO);
+        assert!(unlocked.validate().is_ok());
+    }
+
+    #[test]
+    fn test_with_output_order() {
+        let mut builder = TransactionBuilder::new();
+        builder.add_input("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0);
+
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet);
+        builder.add_output(&address, 1000).unwrap();
+        builder.add_output(&address, 2000).unwrap();
+        builder.add_output(&address, 3000).unwrap();
+
+        assert!(builder.with_output_order(&[0, 1]).is_err());
+        assert!(builder.with_output_order(&[0, 0, 1]).is_err());
+        assert!(builder.with_output_order(&[0, 1, 3]).is_err());
+
+        builder.with_output_order(&[2, 0, 1]).unwrap();
+        let values: Vec<u64> = builder.clone().build().output.iter().map(|o| o.value.to_sat()).collect();
+        assert_eq!(values, vec![3000, 1000, 2000]);
+
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        builder.sign_input(0, &secret, &ScriptBuf::new()).unwrap();
+        assert!(matches!(builder.with_output_order(&[0, 1, 2]), Err(TxError::AlreadySigned(0))));
+    }
 }
@@ -0,0 +1,170 @@
+//! `dogecoin:` payment URIs (BIP21 style), as used in QR codes.
+//!
+//! Example: `dogecoin:DAddress?amount=50&label=Tip&message=Thanks`
+
+use std::fmt;
+
+use crate::address::{AddressError, DogeAddress};
+use crate::amount::DogeAmount;
+
+const SCHEME: &str = "dogecoin:";
+
+#[derive(Debug)]
+pub enum UriError {
+    InvalidScheme,
+    InvalidAddress(AddressError),
+    InvalidAmount(String),
+    InvalidEncoding(String),
+    UnsupportedRequiredParam(String),
+}
+
+impl fmt::Display for UriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UriError::InvalidScheme => write!(f, "URI must start with '{SCHEME}'"),
+            UriError::InvalidAddress(e) => write!(f, "invalid address: {e}"),
+            UriError::InvalidAmount(a) => write!(f, "invalid amount: {a}"),
+            UriError::InvalidEncoding(s) => write!(f, "invalid percent-encoding: {s}"),
+            UriError::UnsupportedRequiredParam(p) => write!(f, "unsupported required parameter: {p}"),
+        }
+    }
+}
+
+impl std::error::Error for UriError {}
+
+/// A decoded payment request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: DogeAddress,
+    /// Amount in satoshis
+    pub amount: Option<u64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+impl PaymentRequest {
+    /// Encode back into a `dogecoin:` URI
+    pub fn to_uri(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", DogeAmount::from_sat(amount)));
+        }
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", percent_encode(label)));
+        }
+        if let Some(message) = &self.message {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+
+        let mut uri = format!("{SCHEME}{}", self.address);
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+        uri
+    }
+}
+
+/// Parse a `dogecoin:` URI. The amount is given in DOGE and converted to satoshis.
+pub fn parse(uri: &str) -> Result<PaymentRequest, UriError> {
+    let rest = match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => return Err(UriError::InvalidScheme),
+    };
+
+    let (address_str, query) = match rest.split_once('?') {
+        Some((a, q)) => (a, Some(q)),
+        None => (rest, None),
+    };
+    let address = address_str.parse::<DogeAddress>().map_err(UriError::InvalidAddress)?;
+
+    let mut request = PaymentRequest { address, amount: None, label: None, message: None };
+    for pair in query.unwrap_or("").split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value)?;
+        match key {
+            "amount" => request.amount = Some(parse_doge(&value)?),
+            "label" => request.label = Some(value),
+            "message" => request.message = Some(value),
+            k if k.starts_with("req-") => return Err(UriError::UnsupportedRequiredParam(k.to_string())),
+            _ => {}
+        }
+    }
+
+    Ok(request)
+}
+
+fn parse_doge(s: &str) -> Result<u64, UriError> {
+    DogeAmount::from_doge_str(s)
+        .map(DogeAmount::to_sat)
+        .map_err(|_| UriError::InvalidAmount(s.to_string()))
+}
+
+fn percent_decode(s: &str) -> Result<String, UriError> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3).ok_or_else(|| UriError::InvalidEncoding(s.to_string()))?;
+            out.push(u8::from_str_radix(hex, 16).map_err(|_| UriError::InvalidEncoding(s.to_string()))?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| UriError::InvalidEncoding(s.to_string()))
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network;
+
+    #[test]
+    fn test_parse_uri() {
+        let address = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        let uri = format!("dogecoin:{address}?amount=50.5&label=Tip%20Jar&message=Thanks!&foo=bar");
+
+        let request = parse(&uri).unwrap();
+        assert_eq!(request.address, address);
+        assert_eq!(request.amount, Some(5_050_000_000));
+        assert_eq!(request.label.as_deref(), Some("Tip Jar"));
+        assert_eq!(request.message.as_deref(), Some("Thanks!"));
+
+        let bare = parse(&format!("dogecoin:{address}")).unwrap();
+        assert_eq!(bare.amount, None);
+
+        assert!(matches!(parse(&format!("bitcoin:{address}")), Err(UriError::InvalidScheme)));
+        assert!(matches!(parse("dogecoin:Dnope"), Err(UriError::InvalidAddress(_))));
+        assert!(matches!(parse(&format!("dogecoin:{address}?amount=1.123456789")), Err(UriError::InvalidAmount(_))));
+        assert!(matches!(parse(&format!("dogecoin:{address}?amount=-1")), Err(UriError::InvalidAmount(_))));
+        assert!(matches!(parse(&format!("dogecoin:{address}?req-foo=1")), Err(UriError::UnsupportedRequiredParam(_))));
+    }
+
+    #[test]
+    fn test_uri_roundtrip() {
+        let request = PaymentRequest {
+            address: DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Testnet),
+            amount: Some(12_340_000),
+            label: Some("Much wow & such tip".to_string()),
+            message: None,
+        };
+        let uri = request.to_uri();
+        assert!(uri.contains("amount=0.1234&"));
+        assert_eq!(parse(&uri).unwrap(), request);
+    }
+}
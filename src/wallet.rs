@@ -0,0 +1,498 @@
+//! High-level wallet tying a key, its address and a chain backend together.
+//!
+//! `Wallet::send` does what `main.rs` wires up by hand: fetch UTXOs, select coins,
+//! build, sign and broadcast.
+
+use bitcoin::bip32::{ChildNumber, Xpriv};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use bitcoin::ScriptBuf;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+
+use crate::address::DogeAddress;
+use crate::coinselect::{self, CoinSelectError};
+use crate::explorer::{ChainSoClient, ExplorerUtxo, UtxoProvider};
+use crate::hd;
+use crate::labels::TxLabelStore;
+use crate::message;
+use crate::network::Network;
+use crate::rpc::DogeRpcClient;
+use crate::script::{self, ScriptType};
+use crate::transaction::{TransactionBuilder, TxError, DUST_LIMIT};
+
+/// Lowest fee rate `preflight` accepts, in sat/byte (Dogecoin Core's 0.001 DOGE/kB relay floor)
+pub const PREFLIGHT_MIN_FEE_RATE: u64 = 100;
+/// Highest fee rate `preflight` accepts, in sat/byte (1 DOGE/kB)
+pub const PREFLIGHT_MAX_FEE_RATE: u64 = 100_000;
+/// Largest transaction Dogecoin Core relays as standard
+const MAX_STANDARD_TX_SIZE: usize = 100_000;
+
+#[derive(Debug)]
+pub enum WalletError {
+    Key(String),
+    NetworkMismatch { wallet: Network, address: Network },
+    Backend(String),
+    Selection(CoinSelectError),
+    Labels(std::io::Error),
+    Transaction(TxError),
+    /// A `preflight` check failed
+    Preflight(String),
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletError::Key(e) => write!(f, "key error: {e}"),
+            WalletError::NetworkMismatch { wallet, address } => {
+                write!(f, "address is for {address} but the wallet is on {wallet}")
+            }
+            WalletError::Backend(e) => write!(f, "backend error: {e}"),
+            WalletError::Selection(e) => write!(f, "coin selection failed: {e}"),
+            WalletError::Labels(e) => write!(f, "label store error: {e}"),
+            WalletError::Transaction(e) => write!(f, "transaction error: {e}"),
+            WalletError::Preflight(e) => write!(f, "preflight failed: {e}"),
+        }
+    }
+}
+
+impl Error for WalletError {}
+
+impl From<CoinSelectError> for WalletError {
+    fn from(e: CoinSelectError) -> Self {
+        WalletError::Selection(e)
+    }
+}
+
+impl From<TxError> for WalletError {
+    fn from(e: TxError) -> Self {
+        WalletError::Transaction(e)
+    }
+}
+
+/// Where UTXOs come from and where transactions go
+pub trait WalletBackend: UtxoProvider {
+    /// Broadcast a signed transaction, returning its txid
+    fn broadcast(&self, tx_hex: &str, network: Network) -> Result<String, Box<dyn Error>>;
+}
+
+impl WalletBackend for ChainSoClient {
+    fn broadcast(&self, tx_hex: &str, network: Network) -> Result<String, Box<dyn Error>> {
+        self.send_tx(tx_hex, network)
+    }
+}
+
+/// A local node: UTXOs via `scantxoutset` (or `listunspent`), broadcast via
+/// `sendrawtransaction`. `network` is ignored, as the node only serves its own chain.
+impl WalletBackend for DogeRpcClient {
+    fn broadcast(&self, tx_hex: &str, _network: Network) -> Result<String, Box<dyn Error>> {
+        Ok(self.broadcast_tx(tx_hex)?.txid)
+    }
+}
+
+/// The key a wallet spends with
+pub enum KeySource {
+    Single(SecretKey),
+    /// BIP32 master key; the wallet uses the first receive key of account 0
+    HdMaster(Xpriv),
+}
+
+/// Single-address wallet over a `WalletBackend`
+pub struct Wallet {
+    secret_key: SecretKey,
+    network: Network,
+    backend: Box<dyn WalletBackend>,
+    labels: Option<RefCell<TxLabelStore>>,
+    min_confirmations: u64,
+}
+
+impl Wallet {
+    pub fn new(keys: KeySource, network: Network, backend: impl WalletBackend + 'static) -> Result<Self, WalletError> {
+        let secret_key = match keys {
+            KeySource::Single(secret_key) => secret_key,
+            KeySource::HdMaster(master) => {
+                let key_err = |e: &dyn fmt::Display| WalletError::Key(e.to_string());
+                let mut path = hd::account_path(network, 0).map_err(|e| key_err(&e))?;
+                path = path.child(ChildNumber::from_normal_idx(hd::RECEIVE_CHAIN).map_err(|e| key_err(&e))?);
+                path = path.child(ChildNumber::from_normal_idx(0).map_err(|e| key_err(&e))?);
+                master.derive_priv(&Secp256k1::new(), &path).map_err(|e| key_err(&e))?.private_key
+            }
+        };
+
+        Ok(Self { secret_key, network, backend: Box::new(backend), labels: None, min_confirmations: 1 })
+    }
+
+    /// Record send memos in `labels`
+    pub fn with_label_store(mut self, labels: TxLabelStore) -> Self {
+        self.labels = Some(RefCell::new(labels));
+        self
+    }
+
+    /// Only spend UTXOs with at least `n` confirmations (default 1; 0 allows unconfirmed coins)
+    pub fn with_min_confirmations(mut self, n: u64) -> Self {
+        self.min_confirmations = n;
+        self
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The wallet's receive (and change) address
+    pub fn address(&self) -> DogeAddress {
+        let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), &self.secret_key);
+        DogeAddress::from_pubkey(&public_key, self.network)
+    }
+
+    /// Sign `challenge` to prove control of `address()`, e.g. before a withdrawal.
+    ///
+    /// Returns a base64 message signature that `verify_ownership` (or `verifymessage`) accepts.
+    pub fn prove_ownership(&self, challenge: &str) -> String {
+        message::sign_message(&self.secret_key, challenge)
+    }
+
+    /// Check an ownership proof produced by `prove_ownership`
+    pub fn verify_ownership(address: &DogeAddress, challenge: &str, signature: &str) -> bool {
+        message::verify_message(address, challenge, signature)
+    }
+
+    pub fn utxos(&self) -> Result<Vec<ExplorerUtxo>, WalletError> {
+        self.backend
+            .get_utxos(&self.address().to_string(), self.network)
+            .map_err(|e| WalletError::Backend(e.to_string()))
+    }
+
+    /// Total value of the wallet's UTXOs in satoshis
+    pub fn balance(&self) -> Result<u64, WalletError> {
+        Ok(self.utxos()?.iter().map(|u| u.value_satoshis).sum())
+    }
+
+    /// Strict checks to run on a transaction before broadcasting it.
+    ///
+    /// Every input must spend a distinct UTXO the backend still reports for this wallet, with
+    /// at least the wallet's minimum confirmations; the fee rate must lie between `PREFLIGHT_MIN_FEE_RATE` and
+    /// `PREFLIGHT_MAX_FEE_RATE`; and the transaction must be standard (valid structure,
+    /// no dust outputs, within the standard size).
+    pub fn preflight(&self, builder: &TransactionBuilder) -> Result<(), WalletError> {
+        let fail = |msg: String| Err(WalletError::Preflight(msg));
+        if let Err(e) = builder.validate() {
+            return fail(e.to_string());
+        }
+
+        let utxos = self.utxos()?;
+        let mut seen = std::collections::HashSet::new();
+        let mut inputs_total = 0u64;
+        for (i, input) in builder.inputs().iter().enumerate() {
+            let outpoint = input.previous_output;
+            if !seen.insert(outpoint) {
+                return fail(format!("input {i} spends {outpoint} twice"));
+            }
+            let txid = outpoint.txid.to_string();
+            let Some(utxo) = utxos.iter().find(|u| u.txid == txid && u.vout == outpoint.vout) else {
+                return fail(format!("input {i} spends {outpoint}, which is spent or unknown"));
+            };
+            if utxo.confirmations < self.min_confirmations {
+                return fail(match utxo.confirmations {
+                    0 => format!("input {i} spends unconfirmed {outpoint}"),
+                    n => format!("input {i} spends {outpoint} with {n} confirmations, below the minimum {}", self.min_confirmations),
+                });
+            }
+            inputs_total += utxo.value_satoshis;
+        }
+
+        let Some(fee) = inputs_total.checked_sub(builder.output_total()) else {
+            return fail(format!("outputs total {} sats but inputs only {inputs_total}", builder.output_total()));
+        };
+        let size = builder.estimate_vsize();
+        let fee_rate = fee / size as u64;
+        if !(PREFLIGHT_MIN_FEE_RATE..=PREFLIGHT_MAX_FEE_RATE).contains(&fee_rate) {
+            return fail(format!("fee rate {fee_rate} sat/byte is outside {PREFLIGHT_MIN_FEE_RATE}..={PREFLIGHT_MAX_FEE_RATE}"));
+        }
+
+        if size > MAX_STANDARD_TX_SIZE {
+            return fail(format!("transaction is {size} bytes, above the standard {MAX_STANDARD_TX_SIZE}"));
+        }
+        for (i, output) in builder.outputs().iter().enumerate() {
+            let script_type = script::classify(&output.script_pubkey);
+            if script_type == ScriptType::Unknown {
+                return fail(format!("output {i} has a non-standard script"));
+            }
+            if script_type != ScriptType::NullData && output.value.to_sat() < DUST_LIMIT {
+                return fail(format!("output {i} is dust"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pay `amount` satoshis to `to` at `fee_rate` sat/byte, returning the broadcast txid.
+    ///
+    /// Change returns to `address()`. Only UTXOs with the wallet's minimum confirmations are spent.
+    pub fn send(&self, to: &DogeAddress, amount: u64, fee_rate: u64) -> Result<String, WalletError> {
+        self.send_with_memo(to, amount, fee_rate, None)
+    }
+
+    /// Like `send`, additionally recording `memo` against the txid in the label store
+    pub fn send_with_memo(
+        &self,
+        to: &DogeAddress,
+        amount: u64,
+        fee_rate: u64,
+        memo: Option<&str>,
+    ) -> Result<String, WalletError> {
+        if to.network != self.network {
+            return Err(WalletError::NetworkMismatch { wallet: self.network, address: to.network });
+        }
+
+        let mut utxos = self.utxos()?;
+        utxos.retain(|u| u.confirmations >= self.min_confirmations);
+        let (selected, estimate) = coinselect::plan_payment(&[(to.clone(), amount)], &utxos, fee_rate, self.network)?;
+
+        let mut builder = TransactionBuilder::new();
+        let mut prev_scripts = Vec::with_capacity(selected.len());
+        for u in &selected {
+            let prev_script = ScriptBuf::from_bytes(hex::decode(&u.script_hex).map_err(|e| WalletError::Backend(e.to_string()))?);
+            builder.add_input_with_prevout(&u.txid, u.vout, &prev_script);
+            prev_scripts.push(prev_script);
+        }
+        builder.add_output(to, amount)?;
+        if estimate.change > 0 {
+            builder.add_output(&self.address(), estimate.change)?;
+        }
+        for (i, prev_script) in prev_scripts.iter().enumerate() {
+            builder.sign_input(i, &self.secret_key, prev_script)?;
+        }
+
+        let tx_hex = bitcoin::consensus::encode::serialize_hex(&builder.build());
+        let txid = self
+            .backend
+            .broadcast(&tx_hex, self.network)
+            .map_err(|e| WalletError::Backend(e.to_string()))?;
+
+        if let (Some(memo), Some(labels)) = (memo, &self.labels) {
+            labels.borrow_mut().set_label(&txid, memo).map_err(WalletError::Labels)?;
+        }
+
+        Ok(txid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    use crate::transaction::verify_input;
+
+    struct MockBackend {
+        utxos: Vec<ExplorerUtxo>,
+        broadcasts: RefCell<Vec<String>>,
+    }
+
+    // Shared so tests can inspect broadcasts after handing the backend to the wallet
+    impl UtxoProvider for Rc<MockBackend> {
+        fn get_utxos(&self, _address: &str, _network: Network) -> Result<Vec<ExplorerUtxo>, Box<dyn Error>> {
+            Ok(self.utxos.clone())
+        }
+
+        fn has_history(&self, _address: &str, _network: Network) -> Result<bool, Box<dyn Error>> {
+            Ok(!self.utxos.is_empty())
+        }
+    }
+
+    impl WalletBackend for Rc<MockBackend> {
+        fn broadcast(&self, tx_hex: &str, _network: Network) -> Result<String, Box<dyn Error>> {
+            self.broadcasts.borrow_mut().push(tx_hex.to_string());
+            let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(tx_hex)?;
+            Ok(tx.compute_txid().to_string())
+        }
+    }
+
+    fn funded_backend(address: &DogeAddress, values: &[u64]) -> Rc<MockBackend> {
+        let script_hex = hex::encode(address.script_pubkey().as_bytes());
+        let utxos = values
+            .iter()
+            .enumerate()
+            .map(|(i, &value_satoshis)| ExplorerUtxo {
+                txid: "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553".to_string(),
+                vout: i as u32,
+                value_satoshis,
+                script_hex: script_hex.clone(),
+                confirmations: 10,
+                is_coinbase: false,
+            })
+            .collect();
+        Rc::new(MockBackend { utxos, broadcasts: RefCell::new(Vec::new()) })
+    }
+
+    #[test]
+    fn test_wallet_over_rpc_backend() {
+        use crate::test_util::mock_server;
+        use serde_json::{json, Value};
+
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let script_hex = hex::encode(address.script_pubkey().as_bytes());
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let rpc_ok = |result: Value| json!({ "result": result, "error": null, "id": 1 }).to_string();
+        let (url, requests) = mock_server(vec![
+            rpc_ok(json!({
+                "success": true,
+                "height": 1_000,
+                "unspents": [{ "txid": txid, "vout": 0, "scriptPubKey": script_hex, "amount": 5.0, "height": 990 }]
+            })),
+            rpc_ok(json!("ab".repeat(32))),
+        ]);
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, DogeRpcClient::new(&url, None, None)).unwrap();
+
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        assert_eq!(wallet.send(&to, 100_000_000, 1_000).unwrap(), "ab".repeat(32));
+
+        let scan: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(scan["params"], json!(["start", [format!("addr({address})")]]));
+        let send: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(send["method"], "sendrawtransaction");
+        let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(send["params"][0].as_str().unwrap()).unwrap();
+        assert!(verify_input(&tx, 0, &address.script_pubkey()));
+    }
+
+    #[test]
+    fn test_wallet_send() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let backend = funded_backend(&address, &[300_000_000, 200_000_000]);
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, backend.clone()).unwrap();
+
+        assert_eq!(wallet.address(), address);
+        assert_eq!(wallet.balance().unwrap(), 500_000_000);
+
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let txid = wallet.send(&to, 100_000_000, 1_000).unwrap();
+
+        let broadcasts = backend.broadcasts.borrow();
+        let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(&broadcasts[0]).unwrap();
+        assert_eq!(tx.compute_txid().to_string(), txid);
+        assert_eq!(tx.input.len(), 1);
+        assert!(verify_input(&tx, 0, &address.script_pubkey()));
+        assert_eq!(tx.output[0].value.to_sat(), 100_000_000);
+        assert_eq!(tx.output[1].script_pubkey, address.script_pubkey());
+        let fee = 300_000_000 - 100_000_000 - tx.output[1].value.to_sat();
+        assert_eq!(fee, tx.total_size() as u64 * 1_000);
+
+        let mainnet = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Mainnet);
+        assert!(matches!(wallet.send(&mainnet, 1, 1_000), Err(WalletError::NetworkMismatch { .. })));
+        assert!(matches!(wallet.send(&to, 600_000_000, 1_000), Err(WalletError::Selection(_))));
+    }
+
+    #[test]
+    fn test_wallet_send_with_memo() {
+        let path = std::env::temp_dir().join(format!("doge-hack-wallet-labels-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, funded_backend(&address, &[300_000_000]))
+            .unwrap()
+            .with_label_store(TxLabelStore::open(&path).unwrap());
+
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+        let txid = wallet.send_with_memo(&to, 100_000_000, 1_000, Some("coffee")).unwrap();
+        assert_eq!(TxLabelStore::open(&path).unwrap().get_label(&txid), Some("coffee"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_preflight() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let mut backend = funded_backend(&address, &[300_000_000, 200_000_000]);
+        Rc::get_mut(&mut backend).unwrap().utxos[1].confirmations = 0;
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, backend).unwrap();
+        let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+
+        let spending = |vouts: &[u32], amount| {
+            let mut builder = TransactionBuilder::new();
+            for &vout in vouts {
+                builder.add_input_with_prevout(txid, vout, &address.script_pubkey());
+            }
+            builder.add_output(&to, amount).unwrap();
+            builder
+        };
+        let preflight_err = |builder| match wallet.preflight(&builder) {
+            Err(WalletError::Preflight(e)) => e,
+            other => panic!("expected a preflight failure, got {other:?}"),
+        };
+
+        // 0.002 DOGE fee on ~191 bytes
+        wallet.preflight(&spending(&[0], 299_800_000)).unwrap();
+
+        assert!(preflight_err(spending(&[2], 100_000_000)).contains("spent or unknown"));
+        assert!(preflight_err(spending(&[1], 100_000_000)).contains("unconfirmed"));
+        assert!(preflight_err(spending(&[0, 0], 100_000_000)).contains("twice"));
+        assert!(preflight_err(spending(&[0], 300_000_000)).contains("fee rate 0"));
+        assert!(preflight_err(spending(&[0], 100_000_000)).contains("fee rate"));
+        assert!(preflight_err(spending(&[0], 400_000_000)).contains("inputs only"));
+        let mut dusty = spending(&[0], 299_000_000);
+        dusty.add_output(&address, 500).unwrap();
+        assert!(preflight_err(dusty).contains("output 1 is dust"));
+        assert!(preflight_err(spending(&[], 100)).contains("no inputs"));
+    }
+
+    #[test]
+    fn test_min_confirmations() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let address = DogeAddress::from_pubkey(&PublicKey::from_secret_key(&Secp256k1::new(), &secret), Network::Testnet);
+        let mut backend = funded_backend(&address, &[300_000_000, 200_000_000]);
+        Rc::get_mut(&mut backend).unwrap().utxos[0].confirmations = 3;
+        let to = DogeAddress::from_pubkey_hash(&[0x22; 20], Network::Testnet);
+
+        // The larger UTXO only has 3 confirmations, so a 6-conf wallet must use the other one
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, backend.clone())
+            .unwrap()
+            .with_min_confirmations(6);
+        wallet.send(&to, 100_000_000, 1_000).unwrap();
+        let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(&backend.broadcasts.borrow()[0]).unwrap();
+        assert_eq!(tx.input.len(), 1);
+        assert_eq!(tx.input[0].previous_output.vout, 1);
+        assert!(matches!(wallet.send(&to, 250_000_000, 1_000), Err(WalletError::Selection(_))));
+
+        let mut builder = TransactionBuilder::new();
+        builder.add_input_with_prevout("fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553", 0, &address.script_pubkey());
+        builder.add_output(&to, 299_800_000).unwrap();
+        let err = wallet.preflight(&builder).unwrap_err().to_string();
+        assert!(err.contains("3 confirmations, below the minimum 6"), "{err}");
+
+        // The default of 1 picks the larger, 3-conf UTXO
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Testnet, backend.clone()).unwrap();
+        wallet.preflight(&builder).unwrap();
+        wallet.send(&to, 100_000_000, 1_000).unwrap();
+        let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize_hex(&backend.broadcasts.borrow()[1]).unwrap();
+        assert_eq!(tx.input[0].previous_output.vout, 0);
+    }
+
+    #[test]
+    fn test_ownership_proof() {
+        let secret = SecretKey::from_slice(&b"12345678901234567890123456789012"[..]).unwrap();
+        let backend = funded_backend(&DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet), &[]);
+        let wallet = Wallet::new(KeySource::Single(secret), Network::Mainnet, backend).unwrap();
+
+        let proof = wallet.prove_ownership("withdrawal #42");
+        assert!(Wallet::verify_ownership(&wallet.address(), "withdrawal #42", &proof));
+        assert!(!Wallet::verify_ownership(&wallet.address(), "withdrawal #43", &proof));
+        let other = DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet);
+        assert!(!Wallet::verify_ownership(&other, "withdrawal #42", &proof));
+    }
+
+    #[test]
+    fn test_wallet_hd_master() {
+        let master = Xpriv::new_master(bitcoin::Network::Bitcoin, &[0x42; 32]).unwrap();
+        let backend = funded_backend(&DogeAddress::from_pubkey_hash(&[0x11; 20], Network::Mainnet), &[]);
+        let wallet = Wallet::new(KeySource::HdMaster(master), Network::Mainnet, backend).unwrap();
+
+        let secp = Secp256k1::new();
+        let account = master.derive_priv(&secp, &hd::account_path(Network::Mainnet, 0).unwrap()).unwrap();
+        let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+        assert_eq!(wallet.address(), hd::derive_address(&xpub, hd::RECEIVE_CHAIN, 0, Network::Mainnet).unwrap());
+    }
+}
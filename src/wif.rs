@@ -0,0 +1,293 @@
+//! Wallet Import Format (WIF) private key encoding.
+//!
+//! Layout: `[version byte][32-byte secret][0x01 if compressed]`, Base58Check encoded.
+//! Dogecoin uses 0x9E on mainnet (`Q...`/`6...`) and 0xF1 on testnet (`c...`).
+//!
+//! BIP38 passphrase-encrypted keys (`6P...`) are supported in the non-EC-multiply mode.
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use bitcoin::base58;
+use bitcoin::hashes::{hash160, sha256d, Hash};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use std::fmt;
+
+use crate::address::DogeAddress;
+use crate::network::Network;
+
+#[derive(Debug)]
+pub enum WifError {
+    InvalidBase58Check(String),
+    InvalidLength(usize),
+    UnknownVersionByte(u8),
+    InvalidCompressionFlag(u8),
+    InvalidSecretKey,
+}
+
+impl fmt::Display for WifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WifError::InvalidBase58Check(e) => write!(f, "invalid base58check: {e}"),
+            WifError::InvalidLength(n) => write!(f, "invalid WIF payload length: {n}, expected 33 or 34"),
+            WifError::UnknownVersionByte(b) => write!(f, "unknown WIF version byte: 0x{b:02x}"),
+            WifError::InvalidCompressionFlag(b) => write!(f, "invalid compression flag: 0x{b:02x}, expected 0x01"),
+            WifError::InvalidSecretKey => write!(f, "secret key out of range"),
+        }
+    }
+}
+
+impl std::error::Error for WifError {}
+
+/// Encode a secret key as WIF for the given network
+pub fn encode_wif(secret_key: &SecretKey, network: Network, compressed: bool) -> String {
+    let mut payload = Vec::with_capacity(34);
+    payload.push(network.wif_version_byte());
+    payload.extend_from_slice(&secret_key.secret_bytes());
+    if compressed {
+        payload.push(0x01);
+    }
+    base58::encode_check(&payload)
+}
+
+/// Decode a WIF string into `(secret_key, network, compressed)`
+pub fn decode_wif(wif: &str) -> Result<(SecretKey, Network, bool), WifError> {
+    let decoded = base58::decode_check(wif).map_err(|e| WifError::InvalidBase58Check(e.to_string()))?;
+
+    let compressed = match decoded.len() {
+        33 => false,
+        34 => {
+            if decoded[33] != 0x01 {
+                return Err(WifError::InvalidCompressionFlag(decoded[33]));
+            }
+            true
+        }
+        n => return Err(WifError::InvalidLength(n)),
+    };
+
+    let network = if decoded[0] == Network::Mainnet.wif_version_byte() {
+        Network::Mainnet
+    } else if decoded[0] == Network::Testnet.wif_version_byte() {
+        Network::Testnet
+    } else {
+        return Err(WifError::UnknownVersionByte(decoded[0]));
+    };
+
+    let secret_key = SecretKey::from_slice(&decoded[1..33]).map_err(|_| WifError::InvalidSecretKey)?;
+    Ok((secret_key, network, compressed))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Bip38Error {
+    InvalidBase58Check(String),
+    InvalidLength(usize),
+    /// EC-multiply (`0x0143`) keys and unknown prefixes aren't supported
+    UnsupportedPrefix([u8; 2]),
+    InvalidFlag(u8),
+    WrongPassphrase,
+    InvalidSecretKey,
+}
+
+impl fmt::Display for Bip38Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bip38Error::InvalidBase58Check(e) => write!(f, "invalid base58check: {e}"),
+            Bip38Error::InvalidLength(n) => write!(f, "invalid BIP38 payload length: {n}, expected 39"),
+            Bip38Error::UnsupportedPrefix(p) => write!(f, "unsupported BIP38 prefix: {}", hex::encode(p)),
+            Bip38Error::InvalidFlag(b) => write!(f, "invalid BIP38 flag byte: 0x{b:02x}"),
+            Bip38Error::WrongPassphrase => write!(f, "wrong passphrase"),
+            Bip38Error::InvalidSecretKey => write!(f, "secret key out of range"),
+        }
+    }
+}
+
+impl std::error::Error for Bip38Error {}
+
+const BIP38_PREFIX: [u8; 2] = [0x01, 0x42];
+const BIP38_FLAG_UNCOMPRESSED: u8 = 0xc0;
+const BIP38_FLAG_COMPRESSED: u8 = 0xe0;
+
+/// Encrypt a secret key with a passphrase (BIP38, non-EC-multiply), giving a `6P...` string.
+///
+/// The address checksum is taken over the Dogecoin mainnet P2PKH address, as Dogecoin
+/// paper wallets do. Passphrases are used as UTF-8 bytes without normalization.
+pub fn encrypt_bip38(secret_key: &SecretKey, passphrase: &str, compressed: bool) -> String {
+    encrypt_bip38_with(secret_key, passphrase, compressed, doge_address)
+}
+
+/// Decrypt a BIP38 `6P...` key into `(secret_key, compressed)`
+pub fn decrypt_bip38(encrypted: &str, passphrase: &str) -> Result<(SecretKey, bool), Bip38Error> {
+    decrypt_bip38_with(encrypted, passphrase, doge_address)
+}
+
+fn doge_address(secret_key: &SecretKey, compressed: bool) -> String {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), secret_key);
+    let hash = if compressed {
+        hash160::Hash::hash(&public_key.serialize())
+    } else {
+        hash160::Hash::hash(&public_key.serialize_uncompressed())
+    };
+    DogeAddress::from_pubkey_hash(hash.as_byte_array(), Network::Mainnet).to_string()
+}
+
+fn bip38_keys(passphrase: &str, address_hash: &[u8]) -> ([u8; 32], Aes256) {
+    let params = scrypt::Params::new(14, 8, 8, 64).expect("valid scrypt params");
+    let mut derived = [0u8; 64];
+    scrypt::scrypt(passphrase.as_bytes(), address_hash, &params, &mut derived).expect("valid output length");
+    let half1: [u8; 32] = derived[..32].try_into().expect("32 bytes");
+    (half1, Aes256::new(GenericArray::from_slice(&derived[32..])))
+}
+
+fn encrypt_bip38_with(
+    secret_key: &SecretKey,
+    passphrase: &str,
+    compressed: bool,
+    address: impl Fn(&SecretKey, bool) -> String,
+) -> String {
+    let address_hash = &sha256d::Hash::hash(address(secret_key, compressed).as_bytes())[..4];
+    let (half1, cipher) = bip38_keys(passphrase, address_hash);
+
+    let mut block = secret_key.secret_bytes();
+    for (b, k) in block.iter_mut().zip(half1) {
+        *b ^= k;
+    }
+    for chunk in block.chunks_mut(16) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(chunk));
+    }
+
+    let mut payload = Vec::with_capacity(39);
+    payload.extend_from_slice(&BIP38_PREFIX);
+    payload.push(if compressed { BIP38_FLAG_COMPRESSED } else { BIP38_FLAG_UNCOMPRESSED });
+    payload.extend_from_slice(address_hash);
+    payload.extend_from_slice(&block);
+    base58::encode_check(&payload)
+}
+
+fn decrypt_bip38_with(
+    encrypted: &str,
+    passphrase: &str,
+    address: impl Fn(&SecretKey, bool) -> String,
+) -> Result<(SecretKey, bool), Bip38Error> {
+    let decoded = base58::decode_check(encrypted).map_err(|e| Bip38Error::InvalidBase58Check(e.to_string()))?;
+    if decoded.len() != 39 {
+        return Err(Bip38Error::InvalidLength(decoded.len()));
+    }
+    if decoded[..2] != BIP38_PREFIX {
+        return Err(Bip38Error::UnsupportedPrefix([decoded[0], decoded[1]]));
+    }
+    let compressed = match decoded[2] {
+        BIP38_FLAG_COMPRESSED => true,
+        BIP38_FLAG_UNCOMPRESSED => false,
+        flag => return Err(Bip38Error::InvalidFlag(flag)),
+    };
+
+    let address_hash = &decoded[3..7];
+    let (half1, cipher) = bip38_keys(passphrase, address_hash);
+    let mut block: [u8; 32] = decoded[7..].try_into().expect("32 bytes");
+    for chunk in block.chunks_mut(16) {
+        cipher.decrypt_block(GenericArray::from_mut_slice(chunk));
+    }
+    for (b, k) in block.iter_mut().zip(half1) {
+        *b ^= k;
+    }
+
+    // A wrong passphrase yields an unrelated (or invalid) key, caught by the address checksum
+    let secret_key = SecretKey::from_slice(&block).map_err(|_| Bip38Error::WrongPassphrase)?;
+    if sha256d::Hash::hash(address(&secret_key, compressed).as_bytes())[..4] != *address_hash {
+        return Err(Bip38Error::WrongPassphrase);
+    }
+    Ok((secret_key, compressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_mainnet_wif() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let secret_key = SecretKey::from_slice(&one).unwrap();
+
+        let wif = encode_wif(&secret_key, Network::Mainnet, true);
+        assert_eq!(wif, "QNcdLVw8fHkixm6NNyN6nVwxKek4u7qrioRbQmjxac5TVoTtZuot");
+        assert_eq!(decode_wif(&wif).unwrap(), (secret_key, Network::Mainnet, true));
+    }
+
+    #[test]
+    fn test_round_trip_both_networks() {
+        let secret_key = SecretKey::from_slice(&rand::random::<[u8; 32]>()).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+
+        for network in [Network::Mainnet, Network::Testnet] {
+            for compressed in [true, false] {
+                let wif = encode_wif(&secret_key, network, compressed);
+                assert_eq!(decode_wif(&wif).unwrap(), (secret_key, network, compressed));
+
+                let (address, wif_network) = DogeAddress::from_wif(&wif).unwrap();
+                assert_eq!(wif_network, network);
+                if compressed {
+                    assert_eq!(address, DogeAddress::from_pubkey(&public_key, network));
+                } else {
+                    let hash = hash160::Hash::hash(&public_key.serialize_uncompressed());
+                    assert_eq!(address, DogeAddress::from_pubkey_hash(hash.as_byte_array(), network));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(decode_wif("notawif"), Err(WifError::InvalidBase58Check(_))));
+
+        let address_like = base58::encode_check(&[0x1e; 21]);
+        assert!(matches!(decode_wif(&address_like), Err(WifError::InvalidLength(21))));
+
+        let mut payload = vec![0x80];
+        payload.extend_from_slice(&[0x11; 32]);
+        assert!(matches!(decode_wif(&base58::encode_check(&payload)), Err(WifError::UnknownVersionByte(0x80))));
+    }
+
+    fn bitcoin_address(secret_key: &SecretKey, compressed: bool) -> String {
+        let inner = PublicKey::from_secret_key(&Secp256k1::signing_only(), secret_key);
+        let public_key = bitcoin::PublicKey { compressed, inner };
+        bitcoin::Address::p2pkh(public_key, bitcoin::Network::Bitcoin).to_string()
+    }
+
+    #[test]
+    fn test_bip38_spec_vectors() {
+        // Vectors from BIP38 ("No compression, no EC multiply" / "Compression, no EC multiply")
+        let cases = [
+            (
+                "5KN7MzqK5wt2TP1fQCYyHBtDrXdJuXbUzm4A9rKAteGu3Qi5CVR",
+                "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg",
+                false,
+            ),
+            (
+                "L44B5gGEpqEDRS9vVPz7QT35jcBG2r3CZwSwQ4fCewXAhAhqGVpP",
+                "6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo",
+                true,
+            ),
+        ];
+        for (wif, encrypted, compressed) in cases {
+            let secret_key = bitcoin::PrivateKey::from_wif(wif).unwrap().inner;
+            assert_eq!(encrypt_bip38_with(&secret_key, "TestingOneTwoThree", compressed, bitcoin_address), encrypted);
+            assert_eq!(
+                decrypt_bip38_with(encrypted, "TestingOneTwoThree", bitcoin_address).unwrap(),
+                (secret_key, compressed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_bip38_round_trip() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let encrypted = encrypt_bip38(&secret_key, "much secret", true);
+        assert!(encrypted.starts_with("6P"));
+        assert_eq!(decrypt_bip38(&encrypted, "much secret").unwrap(), (secret_key, true));
+        assert_eq!(decrypt_bip38(&encrypted, "wrong"), Err(Bip38Error::WrongPassphrase));
+
+        let wif = encode_wif(&secret_key, Network::Mainnet, true);
+        assert!(matches!(decrypt_bip38(&wif, "much secret"), Err(Bip38Error::InvalidLength(_))));
+    }
+}
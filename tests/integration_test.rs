@@ -6,7 +6,7 @@ use doge_hack::network::Network;
 fn test_cli_help() {
     // Test that the help command works
     let output = Command::new("cargo")
-        .args(&["run", "--", "--help"])
+        .args(["run", "--", "--help"])
         .output()
         .expect("Failed to execute cargo run");
 
@@ -27,7 +27,7 @@ fn test_cli_help() {
 fn test_cli_gen_key_testnet() {
     // Test generating a keypair on testnet
     let output = Command::new("cargo")
-        .args(&["run", "--", "--network", "testnet", "gen-key"])
+        .args(["run", "--", "--network", "testnet", "gen-key"])
         .output()
         .expect("Failed to execute cargo run");
 
@@ -44,7 +44,7 @@ fn test_cli_gen_key_testnet() {
 fn test_cli_gen_key_mainnet() {
     // Test generating a keypair on mainnet
     let output = Command::new("cargo")
-        .args(&["run", "--", "--network", "mainnet", "gen-key"])
+        .args(["run", "--", "--network", "mainnet", "gen-key"])
         .output()
         .expect("Failed to execute cargo run");
 
@@ -60,7 +60,7 @@ fn test_cli_gen_key_mainnet() {
 fn test_cli_demo_mode() {
     // Test demo mode (original behavior)
     let output = Command::new("cargo")
-        .args(&["run", "--", "demo"])
+        .args(["run", "--", "demo"])
         .output()
         .expect("Failed to execute cargo run");
 
@@ -80,7 +80,7 @@ fn test_cli_address_derivation() {
     let test_secret_key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
     
     let output = Command::new("cargo")
-        .args(&["run", "--", "address", "--secret-key", test_secret_key])
+        .args(["run", "--", "address", "--secret-key", test_secret_key])
         .output()
         .expect("Failed to execute cargo run");
 
@@ -116,11 +116,11 @@ fn test_cli_sign_with_manual_prevout() {
     // Use a fake txid (only needs to be valid hex) and provide prevout params manually.
     let txid = "fb48f9e2068d0674c965e9057b6f87494df9278065a7f98ee591f7d3d7568553";
     let prev_value = 2 * 100_000_000u64; // 2 DOGE
-    let amount = 1 * 100_000_000u64; // 1 DOGE
+    let amount = 100_000_000u64; // 1 DOGE
     let fee = 1_000_000u64; // 0.01 DOGE
 
     let output = Command::new("cargo")
-        .args(&[
+        .args([
             "run",
             "--",
             "sign",